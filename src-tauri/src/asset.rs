@@ -0,0 +1,172 @@
+use chrono::NaiveDate;
+use tauri::{InvokeError, State};
+
+use crate::asset::types::{Asset, AssetClass, Instrument};
+use crate::brokerage_note::types::BrokerageOrder;
+use crate::database::Database;
+use crate::error::Error;
+use crate::import::{parse_date, parse_number};
+use crate::import::spreadsheet::{self, cell};
+use crate::position;
+
+pub(crate) mod db;
+pub(crate) mod types;
+
+const INVALID_INSTRUMENTS_FILE: &str = "INVALID_INSTRUMENTS_FILE";
+
+#[tauri::command(async)]
+pub(crate) fn get_asset_list(database: State<'_, Database>) -> Result<Vec<Asset>, InvokeError> {
+    let connection = database.connection()?;
+
+    Ok(db::select_assets(&connection)?)
+}
+
+// The asset class decides the tax bucket, exemption and rate, so every change to the registry
+// recomputes the realized results and taxes in the same transaction.
+#[tauri::command(async)]
+pub(crate) fn new_asset(database: State<'_, Database>, mut asset: Asset) -> Result<Asset, InvokeError> {
+    let mut connection = database.connection()?;
+
+    let transaction = connection.transaction()
+        .map_err(Error::from)?;
+
+    asset.symbol = asset.symbol.trim().to_uppercase();
+    asset.id = db::insert_asset(&transaction, &asset)?;
+
+    position::update_realized_results(&transaction)?;
+
+    transaction.commit()
+        .map_err(Error::from)?;
+
+    Ok(asset)
+}
+
+#[tauri::command(async)]
+pub(crate) fn update_asset(database: State<'_, Database>, mut asset: Asset) -> Result<(), InvokeError> {
+    let mut connection = database.connection()?;
+
+    let transaction = connection.transaction()
+        .map_err(Error::from)?;
+
+    asset.symbol = asset.symbol.trim().to_uppercase();
+    db::update_asset(&transaction, &asset)?;
+
+    position::update_realized_results(&transaction)?;
+
+    transaction.commit()
+        .map_err(Error::from)?;
+
+    Ok(())
+}
+
+#[tauri::command(async)]
+pub(crate) fn delete_asset(database: State<'_, Database>, asset_id: i64) -> Result<(), InvokeError> {
+    let mut connection = database.connection()?;
+
+    let transaction = connection.transaction()
+        .map_err(Error::from)?;
+
+    db::delete_asset(&transaction, &asset_id)?;
+
+    position::update_realized_results(&transaction)?;
+
+    transaction.commit()
+        .map_err(Error::from)?;
+
+    Ok(())
+}
+
+// B3 "Cadastro de Instrumentos" file. It lists every listed instrument, so the local table is
+// replaced as a whole and then used to fill in the metadata of the registered assets.
+#[tauri::command(async)]
+pub(crate) fn import_instruments(database: State<'_, Database>, file_path: String) -> Result<usize, InvokeError> {
+    let spreadsheet = spreadsheet::read_spreadsheet(&file_path)?;
+
+    let symbol_column = spreadsheet.column(&["TckrSymb", "Código de Negociação", "Codigo de Negociacao", "Ticker"])
+        .ok_or_else(|| Error::Parse(INVALID_INSTRUMENTS_FILE, "coluna de código de negociação não encontrada".to_string()))?;
+    let isin_column = spreadsheet.column(&["ISIN", "Código ISIN", "Codigo ISIN"]);
+    let issuer_column = spreadsheet.column(&["CrpnNm", "Razão Social", "Razao Social", "Emissor"]);
+    let cnpj_column = spreadsheet.column(&["CNPJ", "CNPJ do Emissor"]);
+    let trading_name_column = spreadsheet.column(&["AsstDesc", "Nome de Pregão", "Nome de Pregao"]);
+    let specification_column = spreadsheet.column(&["SpcfctnCd", "Especificação", "Especificacao"]);
+    let category_column = spreadsheet.column(&["SctyCtgyNm", "Categoria"]);
+    let strike_column = spreadsheet.column(&["ExrcPric", "Preço de Exercício", "Preco de Exercicio"]);
+    let expiration_column = spreadsheet.column(&["XprtnDt", "Vencimento"]);
+
+    let optional_cell = |row: &Vec<String>, column: Option<usize>| {
+        column
+            .map(|column| cell(row, column))
+            .filter(|value| !value.is_empty())
+            .map(|value| value.to_string())
+    };
+
+    let instruments: Vec<Instrument> = spreadsheet.rows
+        .iter()
+        .filter(|row| !cell(row, symbol_column).is_empty())
+        .map(|row| {
+            let symbol = cell(row, symbol_column).to_uppercase();
+
+            Instrument {
+                asset_class: AssetClass::from_security_category(category_column.map_or("", |column| cell(row, column)), &symbol),
+                symbol,
+                isin: optional_cell(row, isin_column),
+                issuer_name: optional_cell(row, issuer_column),
+                cnpj: optional_cell(row, cnpj_column),
+                trading_name: optional_cell(row, trading_name_column).map(|value| value.to_uppercase()),
+                // B3 pads the specification, as in "PN      N2"
+                specification: optional_cell(row, specification_column)
+                    .map(|value| value.split_whitespace().collect::<Vec<&str>>().join(" ").to_uppercase()),
+                strike_price: optional_cell(row, strike_column).and_then(|value| parse_number(&value)),
+                expiration_date: optional_cell(row, expiration_column).and_then(|value| parse_instrument_date(&value)),
+            }
+        })
+        .collect();
+
+    if instruments.is_empty() {
+        return Err(Error::Parse(INVALID_INSTRUMENTS_FILE, "nenhum instrumento encontrado".to_string()).into());
+    }
+
+    let mut connection = database.connection()?;
+
+    let transaction = connection.transaction()
+        .map_err(Error::from)?;
+
+    db::delete_instruments(&transaction)?;
+    db::insert_instruments(&transaction, &instruments)?;
+    db::update_assets_from_instruments(&transaction)?;
+
+    position::update_realized_results(&transaction)?;
+
+    transaction.commit()
+        .map_err(Error::from)?;
+
+    Ok(instruments.len())
+}
+
+// Symbols not found in the imported instruments; nothing is reported before the first import
+#[tauri::command(async)]
+pub(crate) fn get_unknown_symbols(database: State<'_, Database>, symbols: Vec<String>) -> Result<Vec<String>, InvokeError> {
+    let connection = database.connection()?;
+
+    if db::count_instruments(&connection)? == 0 {
+        return Ok(Vec::new());
+    }
+
+    let symbols = symbols.iter()
+        .map(|symbol| symbol.trim().to_uppercase())
+        .collect();
+
+    Ok(db::select_unknown_symbols(&connection, &symbols)?)
+}
+
+pub(crate) fn register_order_assets(transaction: &rusqlite::Transaction, orders: &[BrokerageOrder]) -> Result<(), Error> {
+    let symbols = orders.iter()
+        .map(|order| order.symbol.as_str())
+        .collect();
+
+    db::insert_unknown_assets(transaction, &symbols)
+}
+
+fn parse_instrument_date(value: &str) -> Option<NaiveDate> {
+    parse_date(value).or_else(|| NaiveDate::parse_from_str(value, "%Y-%m-%d").ok())
+}
@@ -0,0 +1,276 @@
+use rusqlite::named_params;
+
+use crate::asset::types::{Asset, AssetClass, Instrument};
+use crate::commons::{QueryMapper, ToVec};
+use crate::error::Error;
+
+const ASSET_MAPPER: QueryMapper<Asset> = |row| {
+    Ok(Asset {
+        id: row.get(0)?,
+        symbol: row.get(1)?,
+        asset_class: row.get(2)?,
+        issuer_name: row.get(3)?,
+        cnpj: row.get(4)?,
+        isin: row.get(5)?,
+    })
+};
+
+pub(crate) fn select_assets(connection: &rusqlite::Connection) -> Result<Vec<Asset>, Error> {
+    let mut statement = connection.prepare_cached(
+    "SELECT
+            id,
+            papel,
+            classe,
+            nome_emissor,
+            cnpj,
+            isin
+        FROM ativo
+        ORDER BY papel ASC"
+    )?;
+
+    let asset_rows = statement.query_map([], ASSET_MAPPER)?;
+
+    Ok(asset_rows.to_vec())
+}
+
+pub(crate) fn insert_asset(connection: &rusqlite::Connection, asset: &Asset) -> Result<i64, Error> {
+    let mut statement = connection.prepare_cached(
+        "INSERT INTO ativo (
+            papel,
+            classe,
+            classe_manual,
+            nome_emissor,
+            cnpj,
+            isin
+        ) VALUES (
+            :symbol,
+            :asset_class,
+            1,
+            :issuer_name,
+            :cnpj,
+            :isin
+        )"
+    )?;
+
+    statement.insert(named_params! {
+        ":symbol": asset.symbol,
+        ":asset_class": asset.asset_class,
+        ":issuer_name": asset.issuer_name,
+        ":cnpj": asset.cnpj,
+        ":isin": asset.isin
+    }).map_err(Error::from)
+}
+
+pub(crate) fn insert_unknown_assets(transaction: &rusqlite::Transaction, symbols: &Vec<&str>) -> Result<(), Error> {
+    let mut statement = transaction.prepare_cached(
+        "INSERT OR IGNORE INTO ativo (
+            papel,
+            classe,
+            nome_emissor,
+            cnpj,
+            isin
+        )
+        SELECT
+            :symbol,
+            COALESCE(i.classe, :asset_class),
+            i.nome_emissor,
+            i.cnpj,
+            i.isin
+        FROM (SELECT 1)
+            LEFT JOIN instrumento i ON
+                i.papel = :symbol"
+    )?;
+
+    for symbol in symbols {
+        statement.execute(named_params! {
+            ":symbol": symbol,
+            ":asset_class": AssetClass::guess(symbol)
+        })?;
+    }
+
+    Ok(())
+}
+
+pub(crate) fn update_asset(connection: &rusqlite::Connection, asset: &Asset) -> Result<(), Error> {
+    let mut statement = connection.prepare_cached(
+        "UPDATE ativo SET
+            papel = :symbol,
+            classe_manual = CASE WHEN classe != :asset_class THEN 1 ELSE classe_manual END,
+            classe = :asset_class,
+            nome_emissor = :issuer_name,
+            cnpj = :cnpj,
+            isin = :isin
+        WHERE id = :id"
+    )?;
+
+    statement.execute(named_params! {
+        ":symbol": asset.symbol,
+        ":asset_class": asset.asset_class,
+        ":issuer_name": asset.issuer_name,
+        ":cnpj": asset.cnpj,
+        ":isin": asset.isin,
+        ":id": asset.id
+    })?;
+
+    Ok(())
+}
+
+pub(crate) fn delete_asset(connection: &rusqlite::Connection, asset_id: &i64) -> Result<(), Error> {
+    connection.execute("DELETE FROM ativo WHERE id = :id", named_params! { ":id": asset_id })?;
+
+    Ok(())
+}
+
+pub(crate) fn select_unknown_symbols(connection: &rusqlite::Connection, symbols: &Vec<String>) -> Result<Vec<String>, Error> {
+    let mut statement = connection.prepare_cached("SELECT COUNT(papel) FROM instrumento WHERE papel = :symbol")?;
+
+    let mut unknown_symbols = Vec::new();
+    for symbol in symbols {
+        let count: i64 = statement.query_row(named_params! { ":symbol": symbol }, |row| row.get(0))?;
+
+        if count == 0 {
+            unknown_symbols.push(symbol.to_string());
+        }
+    }
+
+    Ok(unknown_symbols)
+}
+
+// Options share the trading name of their underlying and fractional tickers end in F, so only
+// tickers ending in a digit are considered. None unless exactly one instrument matches.
+pub(crate) fn select_symbol_by_trading_name(connection: &rusqlite::Connection, trading_name: &str, share_class: &str) -> Result<Option<String>, Error> {
+    let mut statement = connection.prepare_cached(
+    "SELECT DISTINCT
+            papel
+        FROM instrumento
+        WHERE
+            nome_pregao = :trading_name AND
+            (especificacao = :share_class OR especificacao LIKE :share_class || ' %') AND
+            classe != :option_class AND
+            papel GLOB '*[0-9]'
+        LIMIT 2"
+    )?;
+
+    let symbols: Vec<String> = statement.query_map(
+        named_params! {
+            ":trading_name": trading_name,
+            ":share_class": share_class,
+            ":option_class": AssetClass::Option
+        },
+        |row| row.get(0)
+    )?.collect::<Result<_, _>>()?;
+
+    Ok(match symbols.as_slice() {
+        [symbol] => Some(symbol.to_string()),
+        _ => None,
+    })
+}
+
+pub(crate) fn count_instruments(connection: &rusqlite::Connection) -> Result<i64, Error> {
+    connection.query_row("SELECT COUNT(papel) FROM instrumento", [], |row| row.get(0))
+        .map_err(Error::from)
+}
+
+pub(crate) fn delete_instruments(transaction: &rusqlite::Transaction) -> Result<(), Error> {
+    transaction.execute("DELETE FROM instrumento", [])?;
+
+    Ok(())
+}
+
+pub(crate) fn insert_instruments(transaction: &rusqlite::Transaction, instruments: &Vec<Instrument>) -> Result<(), Error> {
+    let mut statement = transaction.prepare_cached(
+        "INSERT OR REPLACE INTO instrumento (
+            papel,
+            isin,
+            nome_emissor,
+            cnpj,
+            nome_pregao,
+            especificacao,
+            classe,
+            preco_exercicio,
+            vencimento
+        ) VALUES (
+            :symbol,
+            :isin,
+            :issuer_name,
+            :cnpj,
+            :trading_name,
+            :specification,
+            :asset_class,
+            :strike_price,
+            :expiration_date
+        )"
+    )?;
+
+    for instrument in instruments {
+        statement.execute(named_params! {
+            ":symbol": instrument.symbol,
+            ":isin": instrument.isin,
+            ":issuer_name": instrument.issuer_name,
+            ":cnpj": instrument.cnpj,
+            ":trading_name": instrument.trading_name,
+            ":specification": instrument.specification,
+            ":asset_class": instrument.asset_class,
+            ":strike_price": instrument.strike_price,
+            ":expiration_date": instrument.expiration_date
+        })?;
+    }
+
+    Ok(())
+}
+
+pub(crate) fn update_assets_from_instruments(transaction: &rusqlite::Transaction) -> Result<(), Error> {
+    transaction.execute(
+        "UPDATE ativo SET
+            classe = CASE WHEN classe_manual THEN classe ELSE (SELECT i.classe FROM instrumento i WHERE i.papel = ativo.papel) END,
+            nome_emissor = COALESCE((SELECT i.nome_emissor FROM instrumento i WHERE i.papel = ativo.papel), nome_emissor),
+            cnpj = COALESCE((SELECT i.cnpj FROM instrumento i WHERE i.papel = ativo.papel), cnpj),
+            isin = COALESCE((SELECT i.isin FROM instrumento i WHERE i.papel = ativo.papel), isin)
+        WHERE papel IN (SELECT papel FROM instrumento)",
+        []
+    )?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database;
+
+    fn instrument(symbol: &str, trading_name: &str, specification: &str, asset_class: AssetClass) -> Instrument {
+        Instrument {
+            symbol: symbol.to_string(),
+            isin: None,
+            issuer_name: None,
+            cnpj: None,
+            trading_name: Some(trading_name.to_string()),
+            specification: Some(specification.to_string()),
+            asset_class,
+            strike_price: None,
+            expiration_date: None,
+        }
+    }
+
+    #[test]
+    fn resolves_trading_name_and_class_to_ticker() {
+        let mut connection = database::open_in_memory();
+        let transaction = connection.transaction().unwrap();
+
+        insert_instruments(&transaction, &vec![
+            instrument("PETR3", "PETROBRAS", "ON N2", AssetClass::Stock),
+            instrument("PETR4", "PETROBRAS", "PN N2", AssetClass::Stock),
+            instrument("PETR4F", "PETROBRAS", "PN N2", AssetClass::Stock),
+            instrument("PETRC250", "PETROBRAS", "PN N2", AssetClass::Option),
+            instrument("ITUB4", "ITAUUNIBANCO", "PN N1", AssetClass::Stock),
+            instrument("ITUB5", "ITAUUNIBANCO", "PN N1", AssetClass::Stock),
+        ]).unwrap();
+
+        assert_eq!(select_symbol_by_trading_name(&transaction, "PETROBRAS", "PN").unwrap(), Some("PETR4".to_string()));
+        assert_eq!(select_symbol_by_trading_name(&transaction, "PETROBRAS", "ON").unwrap(), Some("PETR3".to_string()));
+        assert_eq!(select_symbol_by_trading_name(&transaction, "PETROBRAS", "UNT").unwrap(), None);
+        assert_eq!(select_symbol_by_trading_name(&transaction, "VALE", "ON").unwrap(), None);
+        // Ambiguous matches are left to the user
+        assert_eq!(select_symbol_by_trading_name(&transaction, "ITAUUNIBANCO", "PN").unwrap(), None);
+    }
+}
@@ -0,0 +1,170 @@
+use chrono::NaiveDate;
+use rusqlite::types::{FromSql, FromSqlError, FromSqlResult, ToSql, ToSqlOutput, ValueRef};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub(crate) enum AssetClass {
+    Stock,
+    Unit,
+    RealEstateFund,
+    Etf,
+    Bdr,
+    Option,
+    SubscriptionRight,
+    SubscriptionReceipt,
+    Other,
+}
+
+impl AssetClass {
+    // B3 ticker shape: four letters followed by 3 (ON), 4 (PN), 5/6 (PNA/PNB), 32 to 35 and 39
+    // (BDR), 1/2 (rights), 9/10 (receipts) or an option series letter. An 11 may be a FII, an
+    // ETF or a unit, so it stays Other (15%, no exemption) until the class is confirmed.
+    pub(crate) fn guess(symbol: &str) -> Self {
+        let symbol = symbol.trim().to_uppercase();
+
+        if symbol.len() < 5 || !symbol.chars().take(4).all(|char| char.is_ascii_alphabetic()) {
+            return AssetClass::Other;
+        }
+
+        let suffix = &symbol[4..];
+        let is_option_series = suffix.len() > 1 &&
+            suffix.starts_with(|char: char| char.is_ascii_alphabetic()) &&
+            suffix[1..].chars().all(|char| char.is_ascii_digit());
+
+        if is_option_series {
+            return AssetClass::Option;
+        }
+
+        match suffix {
+            "3" | "4" | "5" | "6" | "7" | "8" => AssetClass::Stock,
+            "32" | "33" | "34" | "35" | "39" => AssetClass::Bdr,
+            "1" | "2" => AssetClass::SubscriptionRight,
+            "9" | "10" => AssetClass::SubscriptionReceipt,
+            _ => AssetClass::Other,
+        }
+    }
+
+    // "SctyCtgyNm" column of the B3 instruments file
+    pub(crate) fn from_security_category(category: &str, symbol: &str) -> Self {
+        let category = category.to_uppercase();
+
+        if category.contains("OPTION") {
+            AssetClass::Option
+        } else if category.contains("ETF") {
+            AssetClass::Etf
+        } else if category.contains("BDR") {
+            AssetClass::Bdr
+        } else if category.contains("UNIT") {
+            AssetClass::Unit
+        } else if category.contains("FUNDS") {
+            AssetClass::RealEstateFund
+        } else if category.contains("SHARES") {
+            AssetClass::Stock
+        } else if category.contains("RIGHT") {
+            AssetClass::SubscriptionRight
+        } else if category.contains("RECEIPT") {
+            AssetClass::SubscriptionReceipt
+        } else {
+            AssetClass::guess(symbol)
+        }
+    }
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            AssetClass::Stock => "STOCK",
+            AssetClass::Unit => "UNIT",
+            AssetClass::RealEstateFund => "REAL_ESTATE_FUND",
+            AssetClass::Etf => "ETF",
+            AssetClass::Bdr => "BDR",
+            AssetClass::Option => "OPTION",
+            AssetClass::SubscriptionRight => "SUBSCRIPTION_RIGHT",
+            AssetClass::SubscriptionReceipt => "SUBSCRIPTION_RECEIPT",
+            AssetClass::Other => "OTHER",
+        }
+    }
+}
+
+impl ToSql for AssetClass {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(ToSqlOutput::from(self.as_str()))
+    }
+}
+
+impl FromSql for AssetClass {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        let value = value.as_str()?;
+
+        [
+            AssetClass::Stock,
+            AssetClass::Unit,
+            AssetClass::RealEstateFund,
+            AssetClass::Etf,
+            AssetClass::Bdr,
+            AssetClass::Option,
+            AssetClass::SubscriptionRight,
+            AssetClass::SubscriptionReceipt,
+            AssetClass::Other,
+        ]
+            .iter()
+            .find(|asset_class| asset_class.as_str() == value)
+            .copied()
+            .ok_or(FromSqlError::InvalidType)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub(crate) struct Asset {
+    #[serde(default)]
+    pub(crate) id: i64,
+    pub(crate) symbol: String,
+    pub(crate) asset_class: AssetClass,
+    #[serde(default)]
+    pub(crate) issuer_name: Option<String>,
+    #[serde(default)]
+    pub(crate) cnpj: Option<String>,
+    #[serde(default)]
+    pub(crate) isin: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub(crate) struct Instrument {
+    pub(crate) symbol: String,
+    pub(crate) isin: Option<String>,
+    pub(crate) issuer_name: Option<String>,
+    pub(crate) cnpj: Option<String>,
+    pub(crate) trading_name: Option<String>,
+    pub(crate) specification: Option<String>,
+    pub(crate) asset_class: AssetClass,
+    pub(crate) strike_price: Option<f64>,
+    pub(crate) expiration_date: Option<NaiveDate>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn guess_from_ticker() {
+        assert_eq!(AssetClass::guess("PETR4"), AssetClass::Stock);
+        assert_eq!(AssetClass::guess("vale3"), AssetClass::Stock);
+        assert_eq!(AssetClass::guess("AAPL34"), AssetClass::Bdr);
+        assert_eq!(AssetClass::guess("PETRC250"), AssetClass::Option);
+        assert_eq!(AssetClass::guess("MGLU1"), AssetClass::SubscriptionRight);
+        assert_eq!(AssetClass::guess("MGLU9"), AssetClass::SubscriptionReceipt);
+    }
+
+    #[test]
+    fn eleven_is_not_guessed() {
+        assert_eq!(AssetClass::guess("HGLG11"), AssetClass::Other);
+        assert_eq!(AssetClass::guess("BOVA11"), AssetClass::Other);
+        assert_eq!(AssetClass::guess("TAEE11"), AssetClass::Other);
+    }
+
+    #[test]
+    fn guess_rejects_non_tickers() {
+        assert_eq!(AssetClass::guess("PETR"), AssetClass::Other);
+        assert_eq!(AssetClass::guess("PETROBRAS PN N2"), AssetClass::Other);
+        assert_eq!(AssetClass::guess("1234"), AssetClass::Other);
+    }
+}
@@ -1,9 +1,12 @@
 use rusqlite::named_params;
 use serde::{Deserialize, Serialize};
-use tauri::InvokeError;
+use tauri::{InvokeError, State};
 
-use crate::database;
+use crate::database::Database;
 use crate::commons::{ToVec, QueryMapper};
+use crate::error::{Error, FieldError, REQUIRED};
+
+const BROKER_IN_USE: &str = "BROKER_IN_USE";
 
 #[derive(Serialize, Deserialize, Debug)]
 pub(crate) struct Broker {
@@ -19,29 +22,50 @@ const BROKER_MAPPER: QueryMapper<Broker> = |row| {
 };
 
 #[tauri::command(async)]
-pub(crate) fn get_broker_list() -> Result<Vec<Broker>, InvokeError> {
-    let connection = database::get_connection()?;
+pub(crate) fn get_broker_list(database: State<'_, Database>) -> Result<Vec<Broker>, InvokeError> {
+    let connection = database.connection()?;
 
-    let mut statement = connection.prepare("SELECT id, nome FROM corretora ORDER BY id ASC")
-        .map_err(|error| InvokeError::from(format!("{}", error)))?;
+    Ok(select_brokers(&connection)?)
+}
 
+#[tauri::command(async)]
+pub(crate) fn new_broker(database: State<'_, Database>, broker_name: String) -> Result<Broker, InvokeError> {
+    validate_broker_name(&broker_name)?;
 
-    let broker_rows = statement
-        .query_map([], BROKER_MAPPER)
-        .map_err(|error| InvokeError::from(format!("{}", error)))?;
+    let connection = database.connection()?;
 
-    Ok(broker_rows.to_vec())
+    Ok(insert_broker(&connection, broker_name)?)
+}
+
+#[tauri::command(async)]
+pub(crate) fn update_broker(database: State<'_, Database>, broker: Broker) -> Result<(), InvokeError> {
+    validate_broker_name(&broker.name)?;
+
+    let connection = database.connection()?;
+
+    Ok(update_broker_name(&connection, &broker)?)
 }
 
+// Notes and income keep their broker, so a broker still referenced by them can't go away
 #[tauri::command(async)]
-pub(crate) fn new_broker(broker_name: String) -> Result<Broker, InvokeError> {
-    let connection = database::get_connection()?;
+pub(crate) fn delete_broker(database: State<'_, Database>, broker_id: i64) -> Result<(), InvokeError> {
+    let connection = database.connection()?;
 
-    let mut statement = connection.prepare("INSERT INTO corretora (nome) VALUES (:name)")
-        .map_err(|error| InvokeError::from(format!("{}", error)))?;
+    Ok(delete_unused_broker(&connection, &broker_id)?)
+}
+
+pub(crate) fn select_brokers(connection: &rusqlite::Connection) -> Result<Vec<Broker>, Error> {
+    let mut statement = connection.prepare_cached("SELECT id, nome FROM corretora ORDER BY id ASC")?;
+
+    let broker_rows = statement.query_map([], BROKER_MAPPER)?;
 
-    let id = statement.insert(named_params! { ":name": &broker_name })
-        .map_err(|error| InvokeError::from(format!("{}", error)))?;
+    Ok(broker_rows.to_vec())
+}
+
+pub(crate) fn insert_broker(connection: &rusqlite::Connection, broker_name: String) -> Result<Broker, Error> {
+    let mut statement = connection.prepare_cached("INSERT INTO corretora (nome) VALUES (:name)")?;
+
+    let id = statement.insert(named_params! { ":name": &broker_name })?;
 
     Ok(Broker {
         id,
@@ -49,15 +73,51 @@ pub(crate) fn new_broker(broker_name: String) -> Result<Broker, InvokeError> {
     })
 }
 
-#[tauri::command(async)]
-pub(crate) fn update_broker(broker: Broker) -> Result<(), InvokeError> {
-    let connection = database::get_connection()?;
+pub(crate) fn select_broker_by_name(connection: &rusqlite::Connection, broker_name: &str) -> Result<Option<Broker>, Error> {
+    let mut statement = connection.prepare_cached("SELECT id, nome FROM corretora WHERE UPPER(nome) = UPPER(:name)")?;
+
+    let mut broker_rows = statement.query_map(named_params! { ":name": broker_name }, BROKER_MAPPER)?;
 
-    let mut statement = connection.prepare("UPDATE corretora SET nome = :name WHERE id = :id")
-        .map_err(|error| InvokeError::from(format!("{}", error)))?;
+    Ok(broker_rows.next().transpose()?)
+}
+
+pub(crate) fn exists_broker(connection: &rusqlite::Connection, broker_id: &i64) -> Result<bool, Error> {
+    let mut statement = connection.prepare_cached("SELECT EXISTS(SELECT 1 FROM corretora WHERE id = :id)")?;
+
+    Ok(statement.query_row(named_params! { ":id": broker_id }, |row| row.get(0))?)
+}
+
+fn update_broker_name(connection: &rusqlite::Connection, broker: &Broker) -> Result<(), Error> {
+    let mut statement = connection.prepare_cached("UPDATE corretora SET nome = :name WHERE id = :id")?;
+
+    statement.execute(named_params! { ":id": &broker.id, ":name": &broker.name })?;
+
+    Ok(())
+}
+
+fn delete_unused_broker(connection: &rusqlite::Connection, broker_id: &i64) -> Result<(), Error> {
+    let mut statement = connection.prepare_cached("
+        SELECT (SELECT COUNT(*) FROM nota_corretagem WHERE corretora_id = :id)
+             + (SELECT COUNT(*) FROM provento WHERE corretora_id = :id)
+    ")?;
+
+    let references: i64 = statement.query_row(named_params! { ":id": broker_id }, |row| row.get(0))?;
+
+    if references > 0 {
+        return Err(Error::ConstraintViolation(BROKER_IN_USE));
+    }
+
+    let mut statement = connection.prepare_cached("DELETE FROM corretora WHERE id = :id")?;
+
+    statement.execute(named_params! { ":id": broker_id })?;
+
+    Ok(())
+}
 
-    statement.execute(named_params! { ":id": &broker.id, ":name": &broker.name })
-        .map_err(|error| InvokeError::from(format!("{}", error)))?;
+fn validate_broker_name(broker_name: &str) -> Result<(), Error> {
+    if broker_name.trim().is_empty() {
+        return Err(Error::Validation(vec![FieldError::new("name", REQUIRED)]));
+    }
 
     Ok(())
 }
@@ -1,15 +1,19 @@
-use tauri::InvokeError;
+use tauri::{InvokeError, State};
 
+use crate::asset;
+use crate::broker;
 use crate::brokerage_note::types::{Brokerage, BrokerageNote, BrokerageOrder};
 use crate::commons::page::{PageRequest, PageResponse};
-use crate::database;
+use crate::database::Database;
+use crate::error::{Error, FieldError, NOT_FOUND};
+use crate::position;
 
-mod types;
-mod db;
+pub(crate) mod types;
+pub(crate) mod db;
 
 #[tauri::command(async)]
-pub(crate) fn get_brokerage_note_page(page_request: PageRequest, broker_id: Option<i64>) -> Result<PageResponse<Brokerage>, InvokeError> {
-    let connection = database::get_connection()?;
+pub(crate) fn get_brokerage_note_page(database: State<'_, Database>, page_request: PageRequest, broker_id: Option<i64>) -> Result<PageResponse<Brokerage>, InvokeError> {
+    let connection = database.connection()?;
 
     let brokerage_list = match broker_id {
         Some(id) => db::select_brokerage_note_page_by_broker(&connection, &page_request, &id),
@@ -27,67 +31,103 @@ pub(crate) fn get_brokerage_note_page(page_request: PageRequest, broker_id: Opti
 }
 
 #[tauri::command(async)]
-pub(crate) fn new_brokerage_note(mut brokerage_note: BrokerageNote) -> Result<(), InvokeError> {
-    brokerage_note.calc();
+pub(crate) fn new_brokerage_note(database: State<'_, Database>, mut brokerage_note: BrokerageNote) -> Result<(), InvokeError> {
+    let mut connection = database.connection()?;
 
-    let mut connection = database::get_connection()?;
+    validate_brokerage_note(&connection, &brokerage_note)?;
+    brokerage_note.calc();
 
     let transaction = connection.transaction()
-        .map_err(|error| InvokeError::from(format!("{}", error)))?;
+        .map_err(Error::from)?;
 
     let brokerage_id = db::insert_new_brokerage(&transaction, &brokerage_note.brokerage)?;
 
     db::insert_new_brokerage_orders(&transaction, &brokerage_id, &brokerage_note.orders)?;
+    asset::register_order_assets(&transaction, &brokerage_note.orders)?;
+
+    position::update_realized_results(&transaction)?;
 
     transaction.commit()
-        .map_err(|error| InvokeError::from(format!("{}", error)))
+        .map_err(Error::from)?;
+
+    Ok(())
 }
 
 #[tauri::command(async)]
-pub(crate) fn update_brokerage_note(mut brokerage_note: BrokerageNote) -> Result<BrokerageNote, InvokeError> {
-    brokerage_note.calc();
+pub(crate) fn update_brokerage_note(database: State<'_, Database>, mut brokerage_note: BrokerageNote) -> Result<BrokerageNote, InvokeError> {
+    let mut connection = database.connection()?;
 
-    let mut connection = database::get_connection()?;
+    validate_brokerage_note(&connection, &brokerage_note)?;
+    brokerage_note.calc();
 
     let transaction = connection.transaction()
-        .map_err(|error| InvokeError::from(format!("{}", error)))?;
+        .map_err(Error::from)?;
 
     db::update_brokerage(&transaction, &brokerage_note.brokerage)?;
 
     db::delete_brokerage_orders(&transaction, &brokerage_note.brokerage.id)?;
     db::insert_new_brokerage_orders(&transaction, &brokerage_note.brokerage.id, &brokerage_note.orders)?;
+    asset::register_order_assets(&transaction, &brokerage_note.orders)?;
+
+    position::update_realized_results(&transaction)?;
 
     transaction.commit()
-        .map_err(|error| InvokeError::from(format!("{}", error)));
+        .map_err(Error::from)?;
 
-    get_brokerage_note(brokerage_note.brokerage.id)
+    Ok(select_brokerage_note(&connection, &brokerage_note.brokerage.id)?)
 }
 
 #[tauri::command(async)]
-pub(crate) fn delete_brokerage_note(brokerage_id: i64) -> Result<(), InvokeError> {
-    let mut connection = database::get_connection()?;
+pub(crate) fn delete_brokerage_note(database: State<'_, Database>, brokerage_id: i64) -> Result<(), InvokeError> {
+    let mut connection = database.connection()?;
 
     let transaction = connection.transaction()
-        .map_err(|error| InvokeError::from(format!("{}", error)))?;
+        .map_err(Error::from)?;
 
-    db::delete_brokerage_orders(&transaction, &brokerage_id)?;
     db::delete_brokerage(&transaction, &brokerage_id)?;
 
+    position::update_realized_results(&transaction)?;
+
     transaction.commit()
-        .map_err(|error| InvokeError::from(format!("{}", error)))
+        .map_err(Error::from)?;
+
+    Ok(())
 }
 
 #[tauri::command(async)]
-pub(crate) fn get_brokerage_note(brokerage_id: i64) -> Result<BrokerageNote, InvokeError> {
-    let connection = database::get_connection()?;
+pub(crate) fn get_brokerage_note(database: State<'_, Database>, brokerage_id: i64) -> Result<BrokerageNote, InvokeError> {
+    let connection = database.connection()?;
+
+    Ok(select_brokerage_note(&connection, &brokerage_id)?)
+}
+
+fn validate_brokerage_note(connection: &rusqlite::Connection, brokerage_note: &BrokerageNote) -> Result<(), Error> {
+    let mut errors = brokerage_note.validate();
+
+    if !broker::exists_broker(connection, &brokerage_note.brokerage.broker.id)? {
+        errors.push(FieldError::new("broker", NOT_FOUND));
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(Error::Validation(errors))
+    }
+}
+
+pub(crate) fn select_brokerage_note(connection: &rusqlite::Connection, brokerage_id: &i64) -> Result<BrokerageNote, Error> {
+    let brokerage = db::select_brokerage_by_id(connection, brokerage_id)?;
 
-    let brokerage = db::select_brokerage_by_id(&connection, &brokerage_id)?;
+    let brokerage_orders = db::select_brokerage_orders_by_brokerage_id(connection, brokerage_id)?;
 
-    let brokerage_orders = db::select_brokerage_orders_by_brokerage_id(&connection, &brokerage_id)?;
+    let trade_matches = position::match_trades(
+        brokerage_orders.iter().map(|order| (order.symbol.as_str(), order.order_type.as_str(), order.amount))
+    );
 
     Ok(BrokerageNote {
         brokerage,
-        orders: brokerage_orders
+        orders: brokerage_orders,
+        trade_matches
     })
 }
 
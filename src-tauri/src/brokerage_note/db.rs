@@ -1,11 +1,11 @@
+use chrono::NaiveDate;
 use rusqlite::{named_params};
-use tauri::InvokeError;
 
 use crate::broker;
 use crate::brokerage_note::{Brokerage, BrokerageOrder};
 use crate::commons::page::PageRequest;
 use crate::commons::{ToVec, QueryMapper};
-use std::error::Error;
+use crate::error::Error;
 
 const BROKERAGE_MAPPER: QueryMapper<Brokerage> = |row| {
     Ok(Brokerage {
@@ -14,6 +14,7 @@ const BROKERAGE_MAPPER: QueryMapper<Brokerage> = |row| {
             id: row.get(1)?,
             name: row.get(2)?,
         },
+        note_number: row.get(13)?,
         total_settlement_fee: row.get(3)?,
         total_emolument_fee: row.get(4)?,
         total_broker_fee: row.get(5)?,
@@ -44,8 +45,8 @@ const BROKERAGE_ORDER_MAPPER: QueryMapper<BrokerageOrder> = |row| {
     })
 };
 
-pub(crate) fn select_brokerage_note_page(connection: &rusqlite::Connection, page_request: &PageRequest) -> Result<Vec<Brokerage>, InvokeError> {
-    let mut statement = connection.prepare(
+pub(crate) fn select_brokerage_note_page(connection: &rusqlite::Connection, page_request: &PageRequest) -> Result<Vec<Brokerage>, Error> {
+    let mut statement = connection.prepare_cached(
     format!("
             SELECT
                 nc.id,
@@ -60,7 +61,8 @@ pub(crate) fn select_brokerage_note_page(connection: &rusqlite::Connection, page
                 nc.total_transacionado,
                 nc.total_comprado,
                 nc.total_vendido,
-                nc.data_pregao
+                nc.data_pregao,
+                nc.numero_nota
             FROM nota_corretagem nc
                 INNER JOIN corretora c ON
                     nc.corretora_id = c.id
@@ -68,7 +70,7 @@ pub(crate) fn select_brokerage_note_page(connection: &rusqlite::Connection, page
             LIMIT :size OFFSET :offset",
             page_request.direction
         ).as_str()
-    ).map_err(|error| InvokeError::from(format!("{}", error)))?;
+    )?;
 
     let offset = page_request.page * page_request.size;
 
@@ -78,13 +80,13 @@ pub(crate) fn select_brokerage_note_page(connection: &rusqlite::Connection, page
             ":size": page_request.size,
         },
         BROKERAGE_MAPPER
-    ).map_err(|error| InvokeError::from(format!("{}", error)))?;
+    )?;
 
     Ok(brokerage_rows.to_vec())
 }
 
-pub(crate) fn select_brokerage_note_page_by_broker(connection: &rusqlite::Connection, page_request: &PageRequest, broker_id: &i64) -> Result<Vec<Brokerage>, InvokeError> {
-    let mut statement = connection.prepare(
+pub(crate) fn select_brokerage_note_page_by_broker(connection: &rusqlite::Connection, page_request: &PageRequest, broker_id: &i64) -> Result<Vec<Brokerage>, Error> {
+    let mut statement = connection.prepare_cached(
         format!("
             SELECT
                 nc.id,
@@ -99,7 +101,8 @@ pub(crate) fn select_brokerage_note_page_by_broker(connection: &rusqlite::Connec
                 nc.total_transacionado,
                 nc.total_comprado,
                 nc.total_vendido,
-                nc.data_pregao
+                nc.data_pregao,
+                nc.numero_nota
             FROM nota_corretagem nc
                 INNER JOIN corretora c ON
                     nc.corretora_id = c.id
@@ -109,7 +112,7 @@ pub(crate) fn select_brokerage_note_page_by_broker(connection: &rusqlite::Connec
             LIMIT :size OFFSET :offset",
             page_request.direction
         ).as_str()
-    ).map_err(|error| InvokeError::from(format!("{}", error)))?;
+    )?;
 
     let offset = page_request.page * page_request.size;
 
@@ -120,30 +123,30 @@ pub(crate) fn select_brokerage_note_page_by_broker(connection: &rusqlite::Connec
             ":id": broker_id
         },
         BROKERAGE_MAPPER
-    ).map_err(|error| InvokeError::from(format!("{}", error)))?;
+    )?;
 
     Ok(brokerage_rows.to_vec())
 }
 
-pub(crate) fn count_total_brokerage(connection: &rusqlite::Connection) -> Result<i64, InvokeError> {
-    let mut statement = connection.prepare("
+pub(crate) fn count_total_brokerage(connection: &rusqlite::Connection) -> Result<i64, Error> {
+    let mut statement = connection.prepare_cached("
     SELECT
         COUNT(nc.id)
     FROM nota_corretagem nc
         INNER JOIN corretora c ON
             nc.corretora_id = c.id
-    ").map_err(|error| InvokeError::from(format!("{}", error)))?;
+    ")?;
 
-    statement.query_row(
+    Ok(statement.query_row(
         [],
         |row: &rusqlite::Row| {
             Ok(row.get(0).unwrap())
         },
-    ).map_err(|error| InvokeError::from(format!("{}", error)))
+    )?)
 }
 
-pub(crate) fn count_total_brokerage_by_broker(connection: &rusqlite::Connection, broker_id: i64) -> Result<i64, InvokeError> {
-    let mut statement = connection.prepare("
+pub(crate) fn count_total_brokerage_by_broker(connection: &rusqlite::Connection, broker_id: i64) -> Result<i64, Error> {
+    let mut statement = connection.prepare_cached("
     SELECT
         COUNT(nc.id)
     FROM nota_corretagem nc
@@ -151,22 +154,23 @@ pub(crate) fn count_total_brokerage_by_broker(connection: &rusqlite::Connection,
             nc.corretora_id = c.id
     WHERE
         nc.corretora_id = :id
-    ").map_err(|error| InvokeError::from(format!("{}", error)))?;
+    ")?;
 
-    statement.query_row(
+    Ok(statement.query_row(
         named_params! {
             ":id": broker_id
         },
         |row: &rusqlite::Row| {
             Ok(row.get(0).unwrap())
         },
-    ).map_err(|error| InvokeError::from(format!("{}", error)))
+    )?)
 }
 
-pub(crate) fn insert_new_brokerage(transaction: &rusqlite::Transaction, brokerage: &Brokerage) -> Result<i64, InvokeError> {
-    let mut brokerage_statement = transaction.prepare(
+pub(crate) fn insert_new_brokerage(transaction: &rusqlite::Transaction, brokerage: &Brokerage) -> Result<i64, Error> {
+    let mut brokerage_statement = transaction.prepare_cached(
         "INSERT INTO nota_corretagem (
             corretora_id,
+            numero_nota,
             total_taxa_liquidacao,
             total_emolumentos,
             total_corretagem,
@@ -179,6 +183,7 @@ pub(crate) fn insert_new_brokerage(transaction: &rusqlite::Transaction, brokerag
             data_pregao
         ) VALUES (
             :broker_id,
+            :note_number,
             :total_settlement_fee,
             :total_emolument_fee,
             :total_broker_fee,
@@ -190,10 +195,11 @@ pub(crate) fn insert_new_brokerage(transaction: &rusqlite::Transaction, brokerag
             :total_sold,
             :trading_date
         )"
-    ).map_err(|error| InvokeError::from(format!("{}", error)))?;
+    )?;
 
-    brokerage_statement.insert(named_params! {
+    Ok(brokerage_statement.insert(named_params! {
         ":broker_id": &brokerage.broker.id,
+        ":note_number": &brokerage.note_number,
         ":total_settlement_fee": &brokerage.total_settlement_fee,
         ":total_emolument_fee": &brokerage.total_emolument_fee,
         ":total_broker_fee": &brokerage.total_broker_fee,
@@ -204,13 +210,14 @@ pub(crate) fn insert_new_brokerage(transaction: &rusqlite::Transaction, brokerag
         ":total_purchased": &brokerage.total_purchased,
         ":total_sold": &brokerage.total_sold,
         ":trading_date": &brokerage.trading_date
-    }).map_err(|error| InvokeError::from(format!("{}", error)))
+    })?)
 }
 
-pub(crate) fn update_brokerage(transaction: &rusqlite::Transaction, brokerage: &Brokerage) -> Result<(), InvokeError> {
-    let mut brokerage_statement = transaction.prepare(
+pub(crate) fn update_brokerage(transaction: &rusqlite::Transaction, brokerage: &Brokerage) -> Result<(), Error> {
+    let mut brokerage_statement = transaction.prepare_cached(
     "UPDATE nota_corretagem SET
             corretora_id = :broker_id,
+            numero_nota = :note_number,
             total_taxa_liquidacao = :total_settlement_fee,
             total_emolumentos = :total_emolument_fee,
             total_corretagem = :total_broker_fee,
@@ -222,13 +229,11 @@ pub(crate) fn update_brokerage(transaction: &rusqlite::Transaction, brokerage: &
             total_vendido = :total_sold,
             data_pregao = :trading_date
         WHERE id = :id"
-    ).map_err(|error| {
-        println!("Preparing statement {}", error);
-        InvokeError::from(format!("{}", error))
-    })?;
+    )?;
 
     brokerage_statement.execute(named_params! {
         ":broker_id": &brokerage.broker.id,
+        ":note_number": &brokerage.note_number,
         ":total_settlement_fee": &brokerage.total_settlement_fee,
         ":total_emolument_fee": &brokerage.total_emolument_fee,
         ":total_broker_fee": &brokerage.total_broker_fee,
@@ -240,16 +245,13 @@ pub(crate) fn update_brokerage(transaction: &rusqlite::Transaction, brokerage: &
         ":total_sold": &brokerage.total_sold,
         ":trading_date": &brokerage.trading_date,
         ":id": &brokerage.id
-    }).map_err(|error| {
-        println!("Executing statement {}", error);
-        InvokeError::from(format!("{}", error))
     })?;
 
     Ok(())
 }
 
-pub(crate) fn insert_new_brokerage_orders(transaction: &rusqlite::Transaction, brokerage_id: &i64, brokerage_orders: &Vec<BrokerageOrder>) -> Result<(), InvokeError> {
-    let mut brokerage_order_statement = transaction.prepare(
+pub(crate) fn insert_new_brokerage_orders(transaction: &rusqlite::Transaction, brokerage_id: &i64, brokerage_orders: &Vec<BrokerageOrder>) -> Result<(), Error> {
+    let mut brokerage_order_statement = transaction.prepare_cached(
         "INSERT INTO nota_corretagem_ordem (
             nota_corretagem_id,
             tipo,
@@ -277,7 +279,7 @@ pub(crate) fn insert_new_brokerage_orders(transaction: &rusqlite::Transaction, b
             :income_tax,
             :total_cost
         )"
-    ).map_err(|error| InvokeError::from(format!("{}", error)))?;
+    )?;
 
     for order in brokerage_orders {
         brokerage_order_statement.insert(named_params! {
@@ -293,35 +295,32 @@ pub(crate) fn insert_new_brokerage_orders(transaction: &rusqlite::Transaction, b
             ":iss_tax": order.iss_tax,
             ":income_tax": order.income_tax,
             ":total_cost": order.total_cost
-        }).map_err(|error| InvokeError::from(format!("{}", error)))?;
+        })?;
     }
 
     Ok(())
 }
 
-pub(crate) fn delete_brokerage_orders(transaction: &rusqlite::Transaction, brokerage_id: &i64) -> Result<(), InvokeError> {
-    let mut statement = transaction.prepare("DELETE FROM nota_corretagem_ordem WHERE nota_corretagem_id = :brokerage_id")
-        .map_err(|error| InvokeError::from(format!("{}", error)))?;
+pub(crate) fn delete_brokerage_orders(transaction: &rusqlite::Transaction, brokerage_id: &i64) -> Result<(), Error> {
+    let mut statement = transaction.prepare_cached("DELETE FROM nota_corretagem_ordem WHERE nota_corretagem_id = :brokerage_id")?;
 
-    statement.execute(named_params! { ":brokerage_id": brokerage_id })
-        .map_err(|error| InvokeError::from(format!("{}", error)))?;
+    statement.execute(named_params! { ":brokerage_id": brokerage_id })?;
 
     Ok(())
 }
 
-pub(crate) fn delete_brokerage(transaction: &rusqlite::Transaction, brokerage_id: &i64) -> Result<(), InvokeError> {
-    let mut statement = transaction.prepare("DELETE FROM nota_corretagem WHERE id = :brokerage_id")
-        .map_err(|error| InvokeError::from(format!("{}", error)))?;
+pub(crate) fn delete_brokerage(transaction: &rusqlite::Transaction, brokerage_id: &i64) -> Result<(), Error> {
+    let mut statement = transaction.prepare_cached("DELETE FROM nota_corretagem WHERE id = :brokerage_id")?;
 
     statement.execute(named_params! {
             ":brokerage_id": brokerage_id
-    }).map_err(|error| InvokeError::from(format!("{}", error)))?;
+    })?;
 
     Ok(())
 }
 
-pub(crate) fn select_brokerage_by_id(connection: &rusqlite::Connection, id: &i64) -> Result<Brokerage, InvokeError> {
-    let mut statement = connection.prepare(
+pub(crate) fn select_brokerage_by_id(connection: &rusqlite::Connection, id: &i64) -> Result<Brokerage, Error> {
+    let mut statement = connection.prepare_cached(
     "SELECT
             nc.id,
             nc.corretora_id,
@@ -335,12 +334,13 @@ pub(crate) fn select_brokerage_by_id(connection: &rusqlite::Connection, id: &i64
             nc.total_transacionado,
             nc.total_comprado,
             nc.total_vendido,
-            nc.data_pregao
+            nc.data_pregao,
+            nc.numero_nota
         FROM nota_corretagem nc
             INNER JOIN corretora c ON
                 nc.corretora_id = c.id
         WHERE nc.id = :id"
-    ).map_err(|error| InvokeError::from(format!("{}", error)))?;
+    )?;
 
     Ok(
         statement.query_row(
@@ -348,12 +348,12 @@ pub(crate) fn select_brokerage_by_id(connection: &rusqlite::Connection, id: &i64
            ":id": id
         },
             BROKERAGE_MAPPER
-        ).map_err(|error| InvokeError::from(format!("{}", error)))?
+        )?
     )
 }
 
-pub(crate) fn select_brokerage_orders_by_brokerage_id(connection: &rusqlite::Connection, brokerage_id: &i64) -> Result<Vec<BrokerageOrder>, InvokeError> {
-    let mut statement = connection.prepare(
+pub(crate) fn select_brokerage_orders_by_brokerage_id(connection: &rusqlite::Connection, brokerage_id: &i64) -> Result<Vec<BrokerageOrder>, Error> {
+    let mut statement = connection.prepare_cached(
     "SELECT
             id,
             tipo,
@@ -369,14 +369,130 @@ pub(crate) fn select_brokerage_orders_by_brokerage_id(connection: &rusqlite::Con
             total_custo
         FROM nota_corretagem_ordem
         WHERE nota_corretagem_id = :brokerage_id"
-    ).map_err(|error| InvokeError::from(format!("{}", error)))?;
+    )?;
 
     let broker_order_rows = statement.query_map(
         named_params! {
             ":brokerage_id": brokerage_id
         },
         BROKERAGE_ORDER_MAPPER
-    ).map_err(|error| InvokeError::from(format!("{}", error)))?;
+    )?;
 
     Ok(broker_order_rows.to_vec())
 }
+
+pub(crate) fn select_brokerage_id_by_broker_and_date(connection: &rusqlite::Connection, broker_id: &i64, trading_date: &NaiveDate) -> Result<Option<i64>, Error> {
+    let mut statement = connection.prepare_cached(
+    "SELECT
+            id
+        FROM nota_corretagem
+        WHERE
+            corretora_id = :broker_id AND
+            DATE(data_pregao) = DATE(:trading_date)
+        ORDER BY id ASC"
+    )?;
+
+    let mut brokerage_rows = statement.query_map(
+        named_params! {
+            ":broker_id": broker_id,
+            ":trading_date": trading_date.format("%Y-%m-%d").to_string()
+        },
+        |row| row.get(0)
+    )?;
+
+    Ok(brokerage_rows.next().transpose()?)
+}
+
+pub(crate) fn select_brokerage_id_by_note_number(connection: &rusqlite::Connection, broker_id: &i64, note_number: &str) -> Result<Option<i64>, Error> {
+    let mut statement = connection.prepare_cached(
+    "SELECT
+            id
+        FROM nota_corretagem
+        WHERE
+            corretora_id = :broker_id AND
+            numero_nota = :note_number"
+    )?;
+
+    let mut brokerage_rows = statement.query_map(
+        named_params! {
+            ":broker_id": broker_id,
+            ":note_number": note_number
+        },
+        |row| row.get(0)
+    )?;
+
+    Ok(brokerage_rows.next().transpose()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Local, TimeZone};
+
+    use super::*;
+    use crate::database;
+
+    fn brokerage(broker_id: i64, note_number: Option<&str>) -> Brokerage {
+        Brokerage {
+            id: 0,
+            broker: broker::Broker {
+                id: broker_id,
+                name: String::new(),
+            },
+            note_number: note_number.map(|note_number| note_number.to_string()),
+            total_settlement_fee: 0.0,
+            total_emolument_fee: 0.0,
+            total_broker_fee: 0.0,
+            total_iss_tax: 0.0,
+            total_income_tax: 0.0,
+            total_cost: 0.0,
+            total_transacted: 0.0,
+            total_purchased: 0.0,
+            total_sold: 0.0,
+            trading_date: Local.ymd(2021, 3, 5).and_hms(0, 0, 0),
+        }
+    }
+
+    #[test]
+    fn note_numbers_are_unique_per_broker() {
+        let mut connection = database::open_in_memory();
+        let transaction = connection.transaction().unwrap();
+
+        let broker = broker::insert_broker(&transaction, "XP".to_string()).unwrap();
+        let other_broker = broker::insert_broker(&transaction, "CLEAR".to_string()).unwrap();
+
+        let brokerage_id = insert_new_brokerage(&transaction, &brokerage(broker.id, Some("87654"))).unwrap();
+        insert_new_brokerage(&transaction, &brokerage(broker.id, Some("87655"))).unwrap();
+        insert_new_brokerage(&transaction, &brokerage(broker.id, None)).unwrap();
+        insert_new_brokerage(&transaction, &brokerage(broker.id, None)).unwrap();
+        insert_new_brokerage(&transaction, &brokerage(other_broker.id, Some("87654"))).unwrap();
+
+        assert_eq!(select_brokerage_id_by_note_number(&transaction, &broker.id, "87654").unwrap(), Some(brokerage_id));
+        assert_eq!(select_brokerage_id_by_note_number(&transaction, &broker.id, "1").unwrap(), None);
+
+        let error = insert_new_brokerage(&transaction, &brokerage(broker.id, Some("87654"))).unwrap_err();
+        assert_eq!(error.code(), "BROKERAGE_NOTE_ALREADY_EXISTS");
+    }
+
+    #[test]
+    fn deleting_a_note_cascades_to_orders_and_results() {
+        let mut connection = database::open_in_memory();
+        let transaction = connection.transaction().unwrap();
+
+        let broker = broker::insert_broker(&transaction, "XP".to_string()).unwrap();
+        let brokerage_id = insert_new_brokerage(&transaction, &brokerage(broker.id, None)).unwrap();
+
+        transaction.execute_batch(&format!("
+            INSERT INTO nota_corretagem_ordem VALUES (1, {brokerage_id}, 'V', 'PETR4', 100, 2500.0, 25.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0);
+            INSERT INTO resultado_operacao VALUES (1, 1, {broker_id}, '2021-03-05', 'PETR4', 100, 20.0, 2000.0, 2500.0, 500.0);
+        ", brokerage_id = brokerage_id, broker_id = broker.id)).unwrap();
+
+        delete_brokerage(&transaction, &brokerage_id).unwrap();
+
+        let count = |table: &str| -> i64 {
+            transaction.query_row(&format!("SELECT COUNT(*) FROM {}", table), [], |row| row.get(0)).unwrap()
+        };
+
+        assert_eq!(count("nota_corretagem_ordem"), 0);
+        assert_eq!(count("resultado_operacao"), 0);
+    }
+}
@@ -2,12 +2,16 @@ use chrono::{DateTime, Local};
 use serde::{Deserialize, Serialize};
 
 use crate::broker;
+use crate::error::{FieldError, FUTURE_DATE, MUST_BE_POSITIVE, REQUIRED, UNKNOWN_VALUE};
+use crate::position::types::TradeMatch;
 
 #[derive(Serialize, Deserialize, Debug)]
 pub(crate) struct Brokerage {
     #[serde(default)]
     pub(crate) id: i64,
     pub(crate) broker: broker::Broker,
+    #[serde(default)]
+    pub(crate) note_number: Option<String>,
     pub(crate) total_settlement_fee: f64,
     pub(crate) total_emolument_fee: f64,
     pub(crate) total_broker_fee: f64,
@@ -54,29 +58,66 @@ pub(crate) struct BrokerageNote {
     #[serde(flatten)]
     pub(crate) brokerage: Brokerage,
     pub(crate) orders: Vec<BrokerageOrder>,
+    #[serde(default)]
+    pub(crate) trade_matches: Vec<TradeMatch>,
 }
 
 impl BrokerageNote {
+    // Checks that don't need the database; every invalid field is reported, not just the first
+    pub(crate) fn validate(&self) -> Vec<FieldError> {
+        let mut errors = Vec::new();
+
+        if self.brokerage.trading_date.date() > Local::today() {
+            errors.push(FieldError::new("trading_date", FUTURE_DATE));
+        }
+
+        if self.orders.is_empty() {
+            errors.push(FieldError::new("orders", REQUIRED));
+        }
+
+        for (index, order) in self.orders.iter().enumerate() {
+            if !matches!(order.order_type.as_str(), "C" | "V") {
+                errors.push(FieldError::new(&format!("orders[{}].order_type", index), UNKNOWN_VALUE));
+            }
+
+            if order.symbol.trim().is_empty() {
+                errors.push(FieldError::new(&format!("orders[{}].symbol", index), REQUIRED));
+            }
+
+            if order.amount <= 0 {
+                errors.push(FieldError::new(&format!("orders[{}].amount", index), MUST_BE_POSITIVE));
+            }
+
+            if order.order_value.is_nan() || order.order_value <= 0.0 {
+                errors.push(FieldError::new(&format!("orders[{}].order_value", index), MUST_BE_POSITIVE));
+            }
+        }
+
+        errors
+    }
+
+    // Also runs on unvalidated import previews, so it must not panic or divide by zero
     pub(crate) fn calc(&mut self) {
         self.brokerage.total_transacted = self.orders
             .iter()
             .map(|order| order.order_value)
-            .reduce(|sum, value| sum + value)
-            .unwrap();
+            .sum();
 
         self.brokerage.total_purchased = 0 as f64;
         self.brokerage.total_sold = 0 as f64;
         self.brokerage.total_income_tax = 0 as f64;
 
-        let order_broker_fee = self.brokerage.total_broker_fee / self.orders.len() as f64;
-        let order_iss_tax = self.brokerage.total_iss_tax / self.orders.len() as f64;
-        let settlement_coefficient = self.brokerage.total_settlement_fee / self.brokerage.total_transacted;
-        let emolument_coefficient = self.brokerage.total_emolument_fee / self.brokerage.total_transacted;
-        self.brokerage.total_cost = self.brokerage.total_settlement_fee +
-            self.brokerage.total_emolument_fee +
-            self.brokerage.total_broker_fee +
-            self.brokerage.total_iss_tax +
-            self.brokerage.total_income_tax;
+        let order_count = self.orders.len().max(1) as f64;
+        let order_broker_fee = self.brokerage.total_broker_fee / order_count;
+        let order_iss_tax = self.brokerage.total_iss_tax / order_count;
+        let (settlement_coefficient, emolument_coefficient) = if self.brokerage.total_transacted != 0.0 {
+            (
+                self.brokerage.total_settlement_fee / self.brokerage.total_transacted,
+                self.brokerage.total_emolument_fee / self.brokerage.total_transacted,
+            )
+        } else {
+            (0.0, 0.0)
+        };
 
         for order in &mut self.orders {
             match order.order_type.as_str() {
@@ -93,14 +134,144 @@ impl BrokerageNote {
 
             order.broker_fee = order_broker_fee;
             order.iss_tax = order_iss_tax;
-            order.unit_value = order.order_value / order.amount as f64;
-            order.settlement_fee = settlement_coefficient / order.order_value;
-            order.emolument_fee = emolument_coefficient / order.order_value;
+            order.unit_value = if order.amount != 0 { order.order_value / order.amount as f64 } else { 0.0 };
+            order.settlement_fee = settlement_coefficient * order.order_value;
+            order.emolument_fee = emolument_coefficient * order.order_value;
             order.total_cost = order.settlement_fee +
                 order.emolument_fee +
                 order.broker_fee +
                 order.iss_tax +
                 order.income_tax;
         }
+
+        // After the orders, which add up the IRRF withheld on sales
+        self.brokerage.total_cost = self.brokerage.total_settlement_fee +
+            self.brokerage.total_emolument_fee +
+            self.brokerage.total_broker_fee +
+            self.brokerage.total_iss_tax +
+            self.brokerage.total_income_tax;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, TimeZone};
+
+    use super::*;
+
+    fn order(order_type: &str, symbol: &str, amount: i64, order_value: f64) -> BrokerageOrder {
+        BrokerageOrder {
+            id: 0,
+            order_type: order_type.to_string(),
+            symbol: symbol.to_string(),
+            amount,
+            order_value,
+            unit_value: 0.0,
+            settlement_fee: 0.0,
+            emolument_fee: 0.0,
+            broker_fee: 0.0,
+            iss_tax: 0.0,
+            income_tax: 0.0,
+            total_cost: 0.0,
+        }
+    }
+
+    fn brokerage_note(trading_date: DateTime<Local>, orders: Vec<BrokerageOrder>) -> BrokerageNote {
+        BrokerageNote {
+            brokerage: Brokerage {
+                id: 0,
+                broker: broker::Broker {
+                    id: 1,
+                    name: "XP".to_string(),
+                },
+                note_number: None,
+                total_settlement_fee: 1.0,
+                total_emolument_fee: 0.2,
+                total_broker_fee: 10.0,
+                total_iss_tax: 0.5,
+                total_income_tax: 0.0,
+                total_cost: 0.0,
+                total_transacted: 0.0,
+                total_purchased: 0.0,
+                total_sold: 0.0,
+                trading_date,
+            },
+            orders,
+            trade_matches: Vec::new(),
+        }
+    }
+
+    fn fields(errors: &[FieldError]) -> Vec<(&str, &str)> {
+        errors.iter().map(|error| (error.field.as_str(), error.code.as_str())).collect()
+    }
+
+    #[test]
+    fn valid_note_has_no_errors() {
+        let note = brokerage_note(Local.ymd(2021, 3, 5).and_hms(0, 0, 0), vec![order("C", "PETR4", 100, 2530.0)]);
+
+        assert!(note.validate().is_empty());
+    }
+
+    #[test]
+    fn reports_every_invalid_field() {
+        let note = brokerage_note(Local::now() + Duration::days(2), vec![
+            order("X", "PETR4", 100, 2530.0),
+            order("C", " ", 0, -1.0),
+            order("V", "VALE3", 10, f64::NAN),
+        ]);
+
+        assert_eq!(fields(&note.validate()), vec![
+            ("trading_date", FUTURE_DATE),
+            ("orders[0].order_type", UNKNOWN_VALUE),
+            ("orders[1].symbol", REQUIRED),
+            ("orders[1].amount", MUST_BE_POSITIVE),
+            ("orders[1].order_value", MUST_BE_POSITIVE),
+            ("orders[2].order_value", MUST_BE_POSITIVE),
+        ]);
+    }
+
+    #[test]
+    fn note_without_orders_is_invalid() {
+        let note = brokerage_note(Local.ymd(2021, 3, 5).and_hms(0, 0, 0), Vec::new());
+
+        assert_eq!(fields(&note.validate()), vec![("orders", REQUIRED)]);
+    }
+
+    #[test]
+    fn fees_are_allocated_to_orders() {
+        let mut note = brokerage_note(Local.ymd(2021, 3, 5).and_hms(0, 0, 0), vec![
+            order("C", "PETR4", 100, 3000.0),
+            order("V", "VALE3", 10, 1000.0),
+        ]);
+
+        note.calc();
+
+        assert_eq!(note.brokerage.total_transacted, 4000.0);
+        assert_eq!(note.brokerage.total_purchased, 3000.0);
+        assert_eq!(note.brokerage.total_sold, 1000.0);
+        assert!((note.brokerage.total_income_tax - 0.05).abs() < 1e-9);
+
+        let (purchase, sale) = (&note.orders[0], &note.orders[1]);
+
+        // Settlement and emolument fees in proportion to the order value, the rest split evenly
+        assert!((purchase.settlement_fee - 0.75).abs() < 1e-9);
+        assert!((sale.settlement_fee - 0.25).abs() < 1e-9);
+        assert!((purchase.emolument_fee - 0.15).abs() < 1e-9);
+        assert_eq!((purchase.broker_fee, sale.broker_fee), (5.0, 5.0));
+        assert_eq!((purchase.iss_tax, sale.iss_tax), (0.25, 0.25));
+        assert_eq!((purchase.income_tax, purchase.unit_value), (0.0, 30.0));
+
+        let order_costs: f64 = note.orders.iter().map(|order| order.total_cost).sum();
+        assert!((order_costs - note.brokerage.total_cost).abs() < 1e-9);
+    }
+
+    #[test]
+    fn calc_does_not_divide_by_zero() {
+        let mut note = brokerage_note(Local.ymd(2021, 3, 5).and_hms(0, 0, 0), vec![order("C", "PETR4", 0, 0.0)]);
+
+        note.calc();
+
+        let order = &note.orders[0];
+        assert!(order.unit_value.is_finite() && order.settlement_fee.is_finite() && order.emolument_fee.is_finite());
     }
 }
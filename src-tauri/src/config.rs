@@ -2,9 +2,11 @@ use std::fs::read_to_string;
 use std::path::Path;
 
 use serde::{Deserialize, Serialize};
-use tauri::{InvokeError, PackageInfo};
+use tauri::PackageInfo;
 use tauri::api::path::resource_dir;
 
+use crate::error::Error;
+
 #[derive(Serialize, Deserialize, Debug)]
 pub(crate) struct AppConfig {
     #[serde(rename = "dataDir")]
@@ -18,7 +20,7 @@ const CANT_READ_RESOURCE_DIR: &str = "CANT_READ_RESOURCE_DIR";
 const CANT_READ_CONFIG_FILE: &str = "CANT_READ_CONFIG_FILE";
 const CANT_PARSE_CONFIG_FILE: &str = "CANT_PARSE_CONFIG_FILE";
 
-pub(crate) fn get_config() -> Result<AppConfig, InvokeError> {
+pub(crate) fn get_config() -> Result<AppConfig, Error> {
     let package_info = PackageInfo {
         name: env!("CARGO_PKG_NAME").to_string(),
         version: env!("CARGO_PKG_VERSION").to_string(),
@@ -29,15 +31,15 @@ pub(crate) fn get_config() -> Result<AppConfig, InvokeError> {
         let config_file = read_to_string(config_file)
             .map_err(|error| {
                 println!("Erro ao ler arquivo de configuracão: {}", error);
-                InvokeError::from(&CANT_READ_CONFIG_FILE)
+                Error::Io(CANT_READ_CONFIG_FILE, error)
             })?;
 
         return serde_json::from_str(&config_file)
             .map_err(|error| {
                 println!("Erro ao fazer parse do arquivo de configuração: {}", error);
-                InvokeError::from(&CANT_PARSE_CONFIG_FILE)
+                Error::Parse(CANT_PARSE_CONFIG_FILE, error.to_string())
             });
     }
 
-    Err(InvokeError::from(&CANT_READ_RESOURCE_DIR))
+    Err(Error::Config(CANT_READ_RESOURCE_DIR))
 }
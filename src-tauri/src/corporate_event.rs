@@ -0,0 +1,101 @@
+use tauri::{InvokeError, State};
+
+use crate::asset;
+use crate::corporate_event::types::CorporateEvent;
+use crate::database::Database;
+use crate::error::Error;
+use crate::position;
+
+pub(crate) mod db;
+pub(crate) mod types;
+
+#[tauri::command(async)]
+pub(crate) fn get_corporate_events(database: State<'_, Database>, symbol: Option<String>) -> Result<Vec<CorporateEvent>, InvokeError> {
+    let connection = database.connection()?;
+
+    Ok(db::select_corporate_events(&connection, &symbol)?)
+}
+
+#[tauri::command(async)]
+pub(crate) fn new_corporate_event(database: State<'_, Database>, mut corporate_event: CorporateEvent) -> Result<CorporateEvent, InvokeError> {
+    let errors = corporate_event.validate();
+    if !errors.is_empty() {
+        return Err(Error::Validation(errors).into());
+    }
+
+    let mut connection = database.connection()?;
+
+    let transaction = connection.transaction()
+        .map_err(Error::from)?;
+
+    normalize_symbols(&mut corporate_event);
+    corporate_event.id = db::insert_corporate_event(&transaction, &corporate_event)?;
+    db::insert_corporate_event_targets(&transaction, &corporate_event.id, &corporate_event.targets)?;
+    register_target_assets(&transaction, &corporate_event)?;
+
+    position::update_realized_results(&transaction)?;
+
+    transaction.commit()
+        .map_err(Error::from)?;
+
+    Ok(corporate_event)
+}
+
+#[tauri::command(async)]
+pub(crate) fn update_corporate_event(database: State<'_, Database>, mut corporate_event: CorporateEvent) -> Result<(), InvokeError> {
+    let errors = corporate_event.validate();
+    if !errors.is_empty() {
+        return Err(Error::Validation(errors).into());
+    }
+
+    let mut connection = database.connection()?;
+
+    let transaction = connection.transaction()
+        .map_err(Error::from)?;
+
+    normalize_symbols(&mut corporate_event);
+    db::update_corporate_event(&transaction, &corporate_event)?;
+    db::delete_corporate_event_targets(&transaction, &corporate_event.id)?;
+    db::insert_corporate_event_targets(&transaction, &corporate_event.id, &corporate_event.targets)?;
+    register_target_assets(&transaction, &corporate_event)?;
+
+    position::update_realized_results(&transaction)?;
+
+    transaction.commit()
+        .map_err(Error::from)?;
+
+    Ok(())
+}
+
+#[tauri::command(async)]
+pub(crate) fn delete_corporate_event(database: State<'_, Database>, corporate_event_id: i64) -> Result<(), InvokeError> {
+    let mut connection = database.connection()?;
+
+    let transaction = connection.transaction()
+        .map_err(Error::from)?;
+
+    db::delete_corporate_event(&transaction, &corporate_event_id)?;
+
+    position::update_realized_results(&transaction)?;
+
+    transaction.commit()
+        .map_err(Error::from)?;
+
+    Ok(())
+}
+
+fn normalize_symbols(corporate_event: &mut CorporateEvent) {
+    corporate_event.symbol = corporate_event.symbol.trim().to_uppercase();
+
+    for target in corporate_event.targets.iter_mut() {
+        target.symbol = target.symbol.trim().to_uppercase();
+    }
+}
+
+fn register_target_assets(transaction: &rusqlite::Transaction, corporate_event: &CorporateEvent) -> Result<(), Error> {
+    let symbols = corporate_event.targets.iter()
+        .map(|target| target.symbol.as_str())
+        .collect();
+
+    asset::db::insert_unknown_assets(transaction, &symbols)
+}
@@ -0,0 +1,202 @@
+use chrono::NaiveDate;
+use rusqlite::named_params;
+
+use crate::commons::{QueryMapper, ToVec};
+use crate::corporate_event::types::{CorporateEvent, CorporateEventTarget, CorporateEventType};
+use crate::error::Error;
+
+const CORPORATE_EVENT_MAPPER: QueryMapper<CorporateEvent> = |row| {
+    Ok(CorporateEvent {
+        id: row.get(0)?,
+        symbol: row.get(1)?,
+        event_type: row.get(2)?,
+        ex_date: row.get(3)?,
+        ratio_from: row.get(4)?,
+        ratio_to: row.get(5)?,
+        unit_cost: row.get(6)?,
+        cash_amount: row.get(7)?,
+        amount: row.get(8)?,
+        targets: Vec::new(),
+    })
+};
+
+const CORPORATE_EVENT_TARGET_MAPPER: QueryMapper<CorporateEventTarget> = |row| {
+    Ok(CorporateEventTarget {
+        id: row.get(0)?,
+        symbol: row.get(1)?,
+        ratio_from: row.get(2)?,
+        ratio_to: row.get(3)?,
+        cost_percentage: row.get(4)?,
+    })
+};
+
+pub(crate) fn select_corporate_events(connection: &rusqlite::Connection, symbol: &Option<String>) -> Result<Vec<CorporateEvent>, Error> {
+    let mut statement = connection.prepare_cached(
+    "SELECT
+            id,
+            papel,
+            tipo,
+            data_ex,
+            proporcao_origem,
+            proporcao_destino,
+            custo_unitario,
+            valor_dinheiro,
+            quantidade
+        FROM evento_corporativo
+        WHERE
+            :symbol IS NULL OR
+            papel = :symbol OR
+            id IN (SELECT evento_corporativo_id FROM evento_corporativo_destino WHERE papel = :symbol)
+        ORDER BY data_ex ASC, id ASC"
+    )?;
+
+    let event_rows = statement.query_map(named_params! { ":symbol": symbol }, CORPORATE_EVENT_MAPPER)?;
+
+    let mut corporate_events = event_rows.to_vec();
+    for corporate_event in corporate_events.iter_mut() {
+        corporate_event.targets = select_corporate_event_targets(connection, &corporate_event.id)?;
+    }
+
+    Ok(corporate_events)
+}
+
+fn select_corporate_event_targets(connection: &rusqlite::Connection, corporate_event_id: &i64) -> Result<Vec<CorporateEventTarget>, Error> {
+    let mut statement = connection.prepare_cached(
+    "SELECT
+            id,
+            papel,
+            proporcao_origem,
+            proporcao_destino,
+            percentual_custo
+        FROM evento_corporativo_destino
+        WHERE evento_corporativo_id = :corporate_event_id
+        ORDER BY id ASC"
+    )?;
+
+    let target_rows = statement.query_map(named_params! { ":corporate_event_id": corporate_event_id }, CORPORATE_EVENT_TARGET_MAPPER)?;
+
+    Ok(target_rows.to_vec())
+}
+
+pub(crate) fn insert_corporate_event(transaction: &rusqlite::Transaction, corporate_event: &CorporateEvent) -> Result<i64, Error> {
+    let mut statement = transaction.prepare_cached(
+        "INSERT INTO evento_corporativo (
+            papel,
+            tipo,
+            data_ex,
+            proporcao_origem,
+            proporcao_destino,
+            custo_unitario,
+            valor_dinheiro,
+            quantidade
+        ) VALUES (
+            :symbol,
+            :event_type,
+            :ex_date,
+            :ratio_from,
+            :ratio_to,
+            :unit_cost,
+            :cash_amount,
+            :amount
+        )"
+    )?;
+
+    statement.insert(named_params! {
+        ":symbol": corporate_event.symbol,
+        ":event_type": corporate_event.event_type,
+        ":ex_date": corporate_event.ex_date,
+        ":ratio_from": corporate_event.ratio_from,
+        ":ratio_to": corporate_event.ratio_to,
+        ":unit_cost": corporate_event.unit_cost,
+        ":cash_amount": corporate_event.cash_amount,
+        ":amount": corporate_event.amount
+    }).map_err(Error::from)
+}
+
+pub(crate) fn update_corporate_event(transaction: &rusqlite::Transaction, corporate_event: &CorporateEvent) -> Result<(), Error> {
+    let mut statement = transaction.prepare_cached(
+        "UPDATE evento_corporativo SET
+            papel = :symbol,
+            tipo = :event_type,
+            data_ex = :ex_date,
+            proporcao_origem = :ratio_from,
+            proporcao_destino = :ratio_to,
+            custo_unitario = :unit_cost,
+            valor_dinheiro = :cash_amount,
+            quantidade = :amount
+        WHERE id = :id"
+    )?;
+
+    statement.execute(named_params! {
+        ":symbol": corporate_event.symbol,
+        ":event_type": corporate_event.event_type,
+        ":ex_date": corporate_event.ex_date,
+        ":ratio_from": corporate_event.ratio_from,
+        ":ratio_to": corporate_event.ratio_to,
+        ":unit_cost": corporate_event.unit_cost,
+        ":cash_amount": corporate_event.cash_amount,
+        ":amount": corporate_event.amount,
+        ":id": corporate_event.id
+    })?;
+
+    Ok(())
+}
+
+pub(crate) fn delete_corporate_event(transaction: &rusqlite::Transaction, corporate_event_id: &i64) -> Result<(), Error> {
+    transaction.execute("DELETE FROM evento_corporativo WHERE id = :id", named_params! { ":id": corporate_event_id })?;
+
+    Ok(())
+}
+
+pub(crate) fn insert_corporate_event_targets(transaction: &rusqlite::Transaction, corporate_event_id: &i64, targets: &Vec<CorporateEventTarget>) -> Result<(), Error> {
+    let mut statement = transaction.prepare_cached(
+        "INSERT INTO evento_corporativo_destino (
+            evento_corporativo_id,
+            papel,
+            proporcao_origem,
+            proporcao_destino,
+            percentual_custo
+        ) VALUES (
+            :corporate_event_id,
+            :symbol,
+            :ratio_from,
+            :ratio_to,
+            :cost_percentage
+        )"
+    )?;
+
+    for target in targets {
+        statement.insert(named_params! {
+            ":corporate_event_id": corporate_event_id,
+            ":symbol": target.symbol,
+            ":ratio_from": target.ratio_from,
+            ":ratio_to": target.ratio_to,
+            ":cost_percentage": target.cost_percentage
+        })?;
+    }
+
+    Ok(())
+}
+
+pub(crate) fn delete_corporate_event_targets(transaction: &rusqlite::Transaction, corporate_event_id: &i64) -> Result<(), Error> {
+    transaction.execute(
+        "DELETE FROM evento_corporativo_destino WHERE evento_corporativo_id = :corporate_event_id",
+        named_params! { ":corporate_event_id": corporate_event_id }
+    )?;
+
+    Ok(())
+}
+
+pub(crate) fn exists_corporate_event(connection: &rusqlite::Connection, symbol: &str, event_type: &CorporateEventType, ex_date: &NaiveDate) -> Result<bool, Error> {
+    let count: i64 = connection.query_row(
+        "SELECT COUNT(id) FROM evento_corporativo WHERE papel = :symbol AND tipo = :event_type AND data_ex = :ex_date",
+        named_params! {
+            ":symbol": symbol,
+            ":event_type": event_type,
+            ":ex_date": ex_date
+        },
+        |row| row.get(0)
+    )?;
+
+    Ok(count > 0)
+}
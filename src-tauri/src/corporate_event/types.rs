@@ -0,0 +1,490 @@
+use chrono::NaiveDate;
+use rusqlite::types::{FromSql, FromSqlError, FromSqlResult, ToSql, ToSqlOutput, ValueRef};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{FieldError, MUST_BE_POSITIVE, MUST_NOT_BE_NEGATIVE, REQUIRED};
+use crate::position::types::{CorporateEventResult, PositionBook};
+
+const TARGETS_NOT_ALLOWED: &str = "TARGETS_NOT_ALLOWED";
+const SINGLE_TARGET_REQUIRED: &str = "SINGLE_TARGET_REQUIRED";
+const INVALID_COST_PERCENTAGE: &str = "INVALID_COST_PERCENTAGE";
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub(crate) enum CorporateEventType {
+    Split,
+    ReverseSplit,
+    Bonus,
+    Merger,
+    SpinOff,
+    TickerChange,
+    SubscriptionRight,
+    SubscriptionExercise,
+    ReceiptConversion,
+}
+
+impl CorporateEventType {
+    fn as_str(&self) -> &'static str {
+        match self {
+            CorporateEventType::Split => "SPLIT",
+            CorporateEventType::ReverseSplit => "REVERSE_SPLIT",
+            CorporateEventType::Bonus => "BONUS",
+            CorporateEventType::Merger => "MERGER",
+            CorporateEventType::SpinOff => "SPIN_OFF",
+            CorporateEventType::TickerChange => "TICKER_CHANGE",
+            CorporateEventType::SubscriptionRight => "SUBSCRIPTION_RIGHT",
+            CorporateEventType::SubscriptionExercise => "SUBSCRIPTION_EXERCISE",
+            CorporateEventType::ReceiptConversion => "RECEIPT_CONVERSION",
+        }
+    }
+}
+
+impl ToSql for CorporateEventType {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(ToSqlOutput::from(self.as_str()))
+    }
+}
+
+impl FromSql for CorporateEventType {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        let value = value.as_str()?;
+
+        [
+            CorporateEventType::Split,
+            CorporateEventType::ReverseSplit,
+            CorporateEventType::Bonus,
+            CorporateEventType::Merger,
+            CorporateEventType::SpinOff,
+            CorporateEventType::TickerChange,
+            CorporateEventType::SubscriptionRight,
+            CorporateEventType::SubscriptionExercise,
+            CorporateEventType::ReceiptConversion,
+        ]
+            .iter()
+            .find(|event_type| event_type.as_str() == value)
+            .copied()
+            .ok_or(FromSqlError::InvalidType)
+    }
+}
+
+// The ratio reads as "ratio_from shares become ratio_to shares": 1:2 for a split, 10:1 for a
+// reverse split and 10:1 for a bonus of one new share for every ten held. Mergers, spin-offs
+// and ticker changes move the position into their targets instead, each with its own ratio.
+//
+// Splits and bonus shares imported from B3 only know how many shares were credited, so an
+// `amount` on them overrides the ratio.
+//
+// Subscription rights are granted on the main ticker at zero cost (targets are the rights),
+// exercised from the rights ticker into a single receipt or share target for `amount` rights
+// (all held when empty) at `unit_cost` per new share, and receipts are converted afterwards.
+#[derive(Serialize, Deserialize, Debug)]
+pub(crate) struct CorporateEvent {
+    #[serde(default)]
+    pub(crate) id: i64,
+    pub(crate) symbol: String,
+    pub(crate) event_type: CorporateEventType,
+    pub(crate) ex_date: NaiveDate,
+    pub(crate) ratio_from: f64,
+    pub(crate) ratio_to: f64,
+    pub(crate) unit_cost: Option<f64>,
+    pub(crate) cash_amount: Option<f64>,
+    pub(crate) amount: Option<i64>,
+    #[serde(default)]
+    pub(crate) targets: Vec<CorporateEventTarget>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub(crate) struct CorporateEventTarget {
+    #[serde(default)]
+    pub(crate) id: i64,
+    pub(crate) symbol: String,
+    pub(crate) ratio_from: f64,
+    pub(crate) ratio_to: f64,
+    pub(crate) cost_percentage: f64,
+}
+
+impl CorporateEvent {
+    pub(crate) fn validate(&self) -> Vec<FieldError> {
+        let mut errors = Vec::new();
+
+        if self.symbol.trim().is_empty() {
+            errors.push(FieldError::new("symbol", REQUIRED));
+        }
+
+        if self.ratio_from.is_nan() || self.ratio_from <= 0.0 {
+            errors.push(FieldError::new("ratio_from", MUST_BE_POSITIVE));
+        }
+
+        if self.ratio_to.is_nan() || self.ratio_to <= 0.0 {
+            errors.push(FieldError::new("ratio_to", MUST_BE_POSITIVE));
+        }
+
+        if matches!(self.unit_cost, Some(unit_cost) if unit_cost.is_nan() || unit_cost < 0.0) {
+            errors.push(FieldError::new("unit_cost", MUST_NOT_BE_NEGATIVE));
+        }
+
+        if matches!(self.cash_amount, Some(cash_amount) if cash_amount.is_nan() || cash_amount < 0.0) {
+            errors.push(FieldError::new("cash_amount", MUST_NOT_BE_NEGATIVE));
+        }
+
+        if matches!(self.amount, Some(amount) if amount <= 0) {
+            errors.push(FieldError::new("amount", MUST_BE_POSITIVE));
+        }
+
+        for (index, target) in self.targets.iter().enumerate() {
+            if target.symbol.trim().is_empty() {
+                errors.push(FieldError::new(&format!("targets[{}].symbol", index), REQUIRED));
+            }
+
+            if target.ratio_from.is_nan() || target.ratio_from <= 0.0 {
+                errors.push(FieldError::new(&format!("targets[{}].ratio_from", index), MUST_BE_POSITIVE));
+            }
+
+            if target.ratio_to.is_nan() || target.ratio_to <= 0.0 {
+                errors.push(FieldError::new(&format!("targets[{}].ratio_to", index), MUST_BE_POSITIVE));
+            }
+
+            if target.cost_percentage.is_nan() || target.cost_percentage < 0.0 {
+                errors.push(FieldError::new(&format!("targets[{}].cost_percentage", index), MUST_NOT_BE_NEGATIVE));
+            }
+        }
+
+        let cost_percentage: f64 = self.targets.iter().map(|target| target.cost_percentage).sum();
+
+        match self.event_type {
+            CorporateEventType::Split | CorporateEventType::ReverseSplit | CorporateEventType::Bonus if !self.targets.is_empty() => {
+                errors.push(FieldError::new("targets", TARGETS_NOT_ALLOWED));
+            }
+            CorporateEventType::Split | CorporateEventType::ReverseSplit | CorporateEventType::Bonus => {}
+            CorporateEventType::SubscriptionExercise => {
+                if self.targets.len() != 1 {
+                    errors.push(FieldError::new("targets", SINGLE_TARGET_REQUIRED));
+                }
+
+                if self.unit_cost.is_none() {
+                    errors.push(FieldError::new("unit_cost", REQUIRED));
+                }
+            }
+            _ if self.targets.is_empty() => {
+                errors.push(FieldError::new("targets", REQUIRED));
+            }
+            CorporateEventType::SpinOff if cost_percentage > 100.0 => {
+                errors.push(FieldError::new("targets", INVALID_COST_PERCENTAGE));
+            }
+            CorporateEventType::Merger | CorporateEventType::TickerChange | CorporateEventType::ReceiptConversion if (cost_percentage - 100.0).abs() >= 0.01 => {
+                errors.push(FieldError::new("targets", INVALID_COST_PERCENTAGE));
+            }
+            CorporateEventType::SubscriptionRight if cost_percentage != 0.0 => {
+                errors.push(FieldError::new("targets", INVALID_COST_PERCENTAGE));
+            }
+            _ => {}
+        }
+
+        errors
+    }
+
+    // Fractions left over are sold by the company in an auction, so amounts are truncated. Only
+    // cash above the cost basis yields a result.
+    pub(crate) fn apply(&self, book: &mut PositionBook) -> Option<CorporateEventResult> {
+        let factor = self.ratio_to / self.ratio_from;
+
+        match self.event_type {
+            CorporateEventType::Split | CorporateEventType::ReverseSplit => {
+                let held_amount = book.amount(&self.symbol);
+                let factor = match self.amount {
+                    Some(amount) if self.event_type == CorporateEventType::Split && held_amount > 0 => (held_amount + amount) as f64 / held_amount as f64,
+                    _ => factor,
+                };
+
+                book.convert(&self.symbol, factor);
+                None
+            }
+            CorporateEventType::Bonus => {
+                let bonus_amount = self.amount.unwrap_or((book.amount(&self.symbol) as f64 * factor).floor() as i64);
+                if bonus_amount > 0 {
+                    book.buy(&self.symbol, bonus_amount, bonus_amount as f64 * self.unit_cost.unwrap_or(0.0));
+                }
+                None
+            }
+            CorporateEventType::Merger | CorporateEventType::SpinOff | CorporateEventType::TickerChange | CorporateEventType::ReceiptConversion =>
+                self.transfer(book),
+            CorporateEventType::SubscriptionRight => {
+                let amount = book.amount(&self.symbol);
+
+                for target in &self.targets {
+                    let right_amount = (amount as f64 * target.ratio_to / target.ratio_from).floor() as i64;
+                    if right_amount > 0 {
+                        book.buy(&target.symbol, right_amount, 0.0);
+                    }
+                }
+                None
+            }
+            CorporateEventType::SubscriptionExercise => {
+                self.exercise(book);
+                None
+            }
+        }
+    }
+
+    // Whatever was paid for the rights plus the subscription price becomes the cost of the new shares
+    fn exercise(&self, book: &mut PositionBook) {
+        let held_amount = book.amount(&self.symbol);
+        let exercised_amount = self.amount.unwrap_or(held_amount).min(held_amount);
+
+        let target = match self.targets.first() {
+            Some(target) if exercised_amount > 0 => target,
+            _ => return,
+        };
+
+        let rights_cost = book.sell(&self.symbol, exercised_amount) * exercised_amount as f64;
+        let subscribed_amount = (exercised_amount as f64 * target.ratio_to / target.ratio_from).floor() as i64;
+
+        book.buy(&target.symbol, subscribed_amount, rights_cost + subscribed_amount as f64 * self.unit_cost.unwrap_or(0.0));
+    }
+
+    // Cash paid per share is a return of capital and is deducted from the cost basis before it
+    // is split among the targets; whatever exceeds the cost basis is a gain. After a spin-off
+    // the source keeps its amount and whatever cost was not moved; otherwise the source
+    // position ceases to exist.
+    fn transfer(&self, book: &mut PositionBook) -> Option<CorporateEventResult> {
+        let amount = book.amount(&self.symbol);
+        if amount <= 0 {
+            return None;
+        }
+
+        let cash = amount as f64 * self.cash_amount.unwrap_or(0.0);
+        let total_invested = book.total_invested(&self.symbol);
+        let cost_basis = (total_invested - cash).max(0.0);
+
+        let mut transferred_cost = 0.0;
+        for target in &self.targets {
+            let target_amount = (amount as f64 * target.ratio_to / target.ratio_from).floor() as i64;
+            let target_cost = cost_basis * target.cost_percentage / 100.0;
+
+            book.buy(&target.symbol, target_amount, target_cost);
+            transferred_cost += target_cost;
+        }
+
+        match self.event_type {
+            CorporateEventType::SpinOff => book.set_total_invested(&self.symbol, cost_basis - transferred_cost),
+            _ => book.close(&self.symbol),
+        }
+
+        if cash <= total_invested {
+            return None;
+        }
+
+        Some(CorporateEventResult {
+            id: 0,
+            corporate_event_id: self.id,
+            ex_date: self.ex_date,
+            symbol: self.symbol.clone(),
+            amount,
+            cost_basis: total_invested,
+            proceeds: cash,
+            result: cash - total_invested,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn corporate_event(symbol: &str, event_type: CorporateEventType, ratio_from: f64, ratio_to: f64) -> CorporateEvent {
+        CorporateEvent {
+            id: 1,
+            symbol: symbol.to_string(),
+            event_type,
+            ex_date: NaiveDate::from_ymd(2021, 3, 1),
+            ratio_from,
+            ratio_to,
+            unit_cost: None,
+            cash_amount: None,
+            amount: None,
+            targets: Vec::new(),
+        }
+    }
+
+    fn target(symbol: &str, ratio_from: f64, ratio_to: f64, cost_percentage: f64) -> CorporateEventTarget {
+        CorporateEventTarget {
+            id: 0,
+            symbol: symbol.to_string(),
+            ratio_from,
+            ratio_to,
+            cost_percentage,
+        }
+    }
+
+    fn fields(errors: &[FieldError]) -> Vec<(&str, &str)> {
+        errors.iter().map(|error| (error.field.as_str(), error.code.as_str())).collect()
+    }
+
+    #[test]
+    fn validation_reports_each_field() {
+        let mut merger = corporate_event(" ", CorporateEventType::Merger, 0.0, f64::NAN);
+        merger.cash_amount = Some(-1.0);
+        merger.targets = vec![target("", 1.0, 0.0, 60.0)];
+
+        assert_eq!(fields(&merger.validate()), vec![
+            ("symbol", REQUIRED),
+            ("ratio_from", MUST_BE_POSITIVE),
+            ("ratio_to", MUST_BE_POSITIVE),
+            ("cash_amount", MUST_NOT_BE_NEGATIVE),
+            ("targets[0].symbol", REQUIRED),
+            ("targets[0].ratio_to", MUST_BE_POSITIVE),
+            ("targets", INVALID_COST_PERCENTAGE),
+        ]);
+    }
+
+    #[test]
+    fn validation_checks_targets_by_event_type() {
+        let mut split = corporate_event("ABCD3", CorporateEventType::Split, 1.0, 2.0);
+        assert!(split.validate().is_empty());
+        split.targets = vec![target("WXYZ3", 1.0, 1.0, 0.0)];
+        assert_eq!(fields(&split.validate()), vec![("targets", TARGETS_NOT_ALLOWED)]);
+
+        let spin_off = corporate_event("ABCD3", CorporateEventType::SpinOff, 1.0, 1.0);
+        assert_eq!(fields(&spin_off.validate()), vec![("targets", REQUIRED)]);
+
+        let mut exercise = corporate_event("ABCD1", CorporateEventType::SubscriptionExercise, 1.0, 1.0);
+        exercise.targets = vec![target("ABCD9", 1.0, 1.0, 100.0)];
+        assert_eq!(fields(&exercise.validate()), vec![("unit_cost", REQUIRED)]);
+    }
+
+    #[test]
+    fn split_and_reverse_split_keep_total_invested() {
+        let mut book = PositionBook::default();
+        book.buy("ABCD3", 100, 1000.0);
+
+        assert!(corporate_event("ABCD3", CorporateEventType::Split, 1.0, 3.0).apply(&mut book).is_none());
+        assert_eq!((book.amount("ABCD3"), book.total_invested("ABCD3")), (300, 1000.0));
+
+        assert!(corporate_event("ABCD3", CorporateEventType::ReverseSplit, 7.0, 1.0).apply(&mut book).is_none());
+        assert_eq!((book.amount("ABCD3"), book.total_invested("ABCD3")), (42, 1000.0));
+    }
+
+    #[test]
+    fn split_with_credited_amount_ignores_ratio() {
+        let mut book = PositionBook::default();
+        book.buy("ABCD3", 100, 1000.0);
+
+        let mut split = corporate_event("ABCD3", CorporateEventType::Split, 1.0, 1.0);
+        split.amount = Some(50);
+
+        assert!(split.apply(&mut book).is_none());
+        assert_eq!((book.amount("ABCD3"), book.total_invested("ABCD3")), (150, 1000.0));
+    }
+
+    #[test]
+    fn bonus_adds_shares_at_unit_cost() {
+        let mut book = PositionBook::default();
+        book.buy("ABCD3", 105, 1050.0);
+
+        let mut bonus = corporate_event("ABCD3", CorporateEventType::Bonus, 10.0, 1.0);
+        bonus.unit_cost = Some(5.0);
+
+        assert!(bonus.apply(&mut book).is_none());
+        assert_eq!((book.amount("ABCD3"), book.total_invested("ABCD3")), (115, 1100.0));
+    }
+
+    #[test]
+    fn subscription_right_is_granted_without_cost() {
+        let mut book = PositionBook::default();
+        book.buy("ABCD3", 105, 1050.0);
+
+        let mut subscription_right = corporate_event("ABCD3", CorporateEventType::SubscriptionRight, 1.0, 1.0);
+        subscription_right.targets = vec![target("ABCD1", 10.0, 1.0, 0.0)];
+
+        assert!(subscription_right.apply(&mut book).is_none());
+        assert_eq!((book.amount("ABCD3"), book.total_invested("ABCD3")), (105, 1050.0));
+        assert_eq!((book.amount("ABCD1"), book.total_invested("ABCD1")), (10, 0.0));
+    }
+
+    #[test]
+    fn exercise_turns_rights_into_receipts_at_subscription_price() {
+        let mut book = PositionBook::default();
+        book.buy("ABCD1", 10, 5.0);
+
+        let mut exercise = corporate_event("ABCD1", CorporateEventType::SubscriptionExercise, 1.0, 1.0);
+        exercise.unit_cost = Some(8.0);
+        exercise.amount = Some(6);
+        exercise.targets = vec![target("ABCD9", 1.0, 1.0, 100.0)];
+
+        assert!(exercise.apply(&mut book).is_none());
+        assert_eq!((book.amount("ABCD1"), book.total_invested("ABCD1")), (4, 2.0));
+        assert_eq!((book.amount("ABCD9"), book.total_invested("ABCD9")), (6, 51.0));
+    }
+
+    #[test]
+    fn receipt_conversion_joins_the_shares_position() {
+        let mut book = PositionBook::default();
+        book.buy("ABCD3", 100, 1000.0);
+        book.buy("ABCD9", 6, 51.0);
+
+        let mut receipt_conversion = corporate_event("ABCD9", CorporateEventType::ReceiptConversion, 1.0, 1.0);
+        receipt_conversion.targets = vec![target("ABCD3", 1.0, 1.0, 100.0)];
+
+        assert!(receipt_conversion.apply(&mut book).is_none());
+        assert_eq!(book.amount("ABCD9"), 0);
+        assert_eq!((book.amount("ABCD3"), book.total_invested("ABCD3")), (106, 1051.0));
+    }
+
+    #[test]
+    fn merger_moves_position_and_cost_into_target() {
+        let mut book = PositionBook::default();
+        book.buy("ABCD3", 100, 1000.0);
+
+        let mut merger = corporate_event("ABCD3", CorporateEventType::Merger, 1.0, 1.0);
+        merger.targets = vec![target("WXYZ3", 2.0, 1.0, 100.0)];
+
+        assert!(merger.apply(&mut book).is_none());
+        assert_eq!(book.amount("ABCD3"), 0);
+        assert_eq!((book.amount("WXYZ3"), book.total_invested("WXYZ3")), (50, 1000.0));
+    }
+
+    #[test]
+    fn cash_within_cost_basis_reduces_it() {
+        let mut book = PositionBook::default();
+        book.buy("ABCD3", 100, 1000.0);
+
+        let mut merger = corporate_event("ABCD3", CorporateEventType::Merger, 1.0, 1.0);
+        merger.cash_amount = Some(2.0);
+        merger.targets = vec![target("WXYZ3", 1.0, 1.0, 100.0)];
+
+        assert!(merger.apply(&mut book).is_none());
+        assert_eq!(book.total_invested("WXYZ3"), 800.0);
+    }
+
+    #[test]
+    fn cash_above_cost_basis_is_a_gain() {
+        let mut book = PositionBook::default();
+        book.buy("ABCD3", 100, 1000.0);
+
+        let mut merger = corporate_event("ABCD3", CorporateEventType::Merger, 1.0, 1.0);
+        merger.cash_amount = Some(15.0);
+        merger.targets = vec![target("WXYZ3", 1.0, 1.0, 100.0)];
+
+        let corporate_event_result = merger.apply(&mut book).unwrap();
+
+        assert_eq!(corporate_event_result.corporate_event_id, 1);
+        assert_eq!(corporate_event_result.amount, 100);
+        assert_eq!(corporate_event_result.cost_basis, 1000.0);
+        assert_eq!(corporate_event_result.proceeds, 1500.0);
+        assert_eq!(corporate_event_result.result, 500.0);
+        assert_eq!((book.amount("WXYZ3"), book.total_invested("WXYZ3")), (100, 0.0));
+    }
+
+    #[test]
+    fn spin_off_keeps_source_and_uncovered_cost() {
+        let mut book = PositionBook::default();
+        book.buy("ABCD3", 100, 1000.0);
+
+        let mut spin_off = corporate_event("ABCD3", CorporateEventType::SpinOff, 1.0, 1.0);
+        spin_off.targets = vec![target("NEWC3", 10.0, 1.0, 30.0)];
+
+        assert!(spin_off.apply(&mut book).is_none());
+        assert_eq!((book.amount("ABCD3"), book.total_invested("ABCD3")), (100, 700.0));
+        assert_eq!((book.amount("NEWC3"), book.total_invested("NEWC3")), (10, 300.0));
+    }
+}
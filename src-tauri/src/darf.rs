@@ -0,0 +1,349 @@
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+use tauri::{InvokeError, State};
+
+use crate::darf::types::{Darf, DarfStatus, LateDarf, SelicRate};
+use crate::database::Database;
+use crate::error::{Error, FieldError};
+use crate::import::{parse_date, parse_decimal};
+use crate::import::spreadsheet::{self, cell};
+use crate::tax;
+use crate::tax::types::MonthlyTax;
+
+pub(crate) mod db;
+pub(crate) mod types;
+
+const INDIVIDUAL_REVENUE_CODE: &str = "6015";
+const MINIMUM_DARF_AMOUNT: f64 = 10.0;
+const DAILY_FINE_RATE: f64 = 0.0033;
+const MAXIMUM_FINE_RATE: f64 = 0.20;
+const PAYMENT_MONTH_INTEREST_RATE: f64 = 0.01;
+const DARF_NOT_PENDING: &str = "DARF_NOT_PENDING";
+const INVALID_SELIC_FILE: &str = "INVALID_SELIC_FILE";
+const SELIC_RATE_NOT_FOUND: &str = "SELIC_RATE_NOT_FOUND";
+const INVALID_COMPETENCE: &str = "INVALID_COMPETENCE";
+
+#[tauri::command(async)]
+pub(crate) fn get_pending_darfs(database: State<'_, Database>) -> Result<Vec<Darf>, InvokeError> {
+    let connection = database.connection()?;
+
+    Ok(db::select_darfs_by_status(&connection, &DarfStatus::Pending)?)
+}
+
+#[tauri::command(async)]
+pub(crate) fn get_darfs(database: State<'_, Database>, year: i32) -> Result<Vec<Darf>, InvokeError> {
+    let connection = database.connection()?;
+
+    Ok(db::select_darfs_by_year(&connection, &year)?)
+}
+
+#[tauri::command(async)]
+pub(crate) fn pay_darf(database: State<'_, Database>, darf_id: i64, payment_date: NaiveDate, paid_amount: f64) -> Result<(), InvokeError> {
+    let connection = database.connection()?;
+
+    match db::update_darf_payment(&connection, &darf_id, &payment_date, &paid_amount)? {
+        0 => Err(Error::NotFound(DARF_NOT_PENDING).into()),
+        _ => Ok(()),
+    }
+}
+
+#[tauri::command(async)]
+pub(crate) fn import_selic_rates(database: State<'_, Database>, file_path: String) -> Result<usize, InvokeError> {
+    let spreadsheet = spreadsheet::read_spreadsheet(&file_path)?;
+
+    let (month_column, rate_column) = match (spreadsheet.column(&["Data", "Mês", "Mes"]), spreadsheet.column(&["Valor", "Taxa"])) {
+        (Some(month_column), Some(rate_column)) => (month_column, rate_column),
+        _ => return Err(Error::Parse(INVALID_SELIC_FILE, "colunas de mês e taxa não encontradas".to_string()).into()),
+    };
+
+    let selic_rates: Vec<SelicRate> = spreadsheet.rows
+        .iter()
+        .filter_map(|row| {
+            Some(SelicRate {
+                month: parse_selic_month(cell(row, month_column))?,
+                rate: parse_decimal(cell(row, rate_column))?,
+            })
+        })
+        .collect();
+
+    if selic_rates.is_empty() {
+        return Err(Error::Parse(INVALID_SELIC_FILE, "nenhuma taxa encontrada".to_string()).into());
+    }
+
+    let mut connection = database.connection()?;
+
+    let transaction = connection.transaction()
+        .map_err(Error::from)?;
+
+    db::insert_or_replace_selic_rates(&transaction, &selic_rates)?;
+
+    transaction.commit()
+        .map_err(Error::from)?;
+
+    Ok(selic_rates.len())
+}
+
+// Fine of 0.33% per day late capped at 20%, plus SELIC accumulated from the month after the
+// due date until the month before the payment and 1% for the payment month itself.
+#[tauri::command(async)]
+pub(crate) fn calc_late_darf(database: State<'_, Database>, principal: f64, competence: String, payment_date: NaiveDate) -> Result<LateDarf, InvokeError> {
+    let due_date = due_date(&competence)
+        .ok_or_else(|| Error::Validation(vec![FieldError::new("competence", INVALID_COMPETENCE)]))?;
+    let days_late = (payment_date - due_date).num_days().max(0);
+
+    let due_month = due_date.format("%Y-%m").to_string();
+    let payment_month = payment_date.format("%Y-%m").to_string();
+
+    let interest_rate = if payment_month > due_month {
+        let connection = database.connection()?;
+
+        let first_month = tax::next_month(&due_month);
+        let selic_rates = db::select_selic_rates_between(&connection, &first_month, &payment_month)?;
+
+        let mut selic_rate = 0.0;
+        let mut month = first_month;
+        while month < payment_month {
+            selic_rate += selic_rates.iter()
+                .find(|selic| selic.month == month)
+                .map(|selic| selic.rate / 100.0)
+                .ok_or_else(|| {
+                    println!("Taxa SELIC não encontrada para o mês {}", month);
+                    Error::NotFound(SELIC_RATE_NOT_FOUND)
+                })?;
+            month = tax::next_month(&month);
+        }
+
+        selic_rate + PAYMENT_MONTH_INTEREST_RATE
+    } else {
+        0.0
+    };
+
+    let fine_rate = (days_late as f64 * DAILY_FINE_RATE).min(MAXIMUM_FINE_RATE);
+    let fine = principal * fine_rate;
+    let interest = principal * interest_rate;
+
+    Ok(LateDarf {
+        principal,
+        due_date,
+        payment_date,
+        days_late,
+        fine_rate,
+        fine,
+        interest_rate,
+        interest,
+        total: principal + fine + interest,
+    })
+}
+
+// Paid DARFs are kept as history; everything else is rebuilt from the monthly taxes, carrying
+// amounts under the R$10 minimum into the following month until they reach it.
+pub(crate) fn update_darfs(transaction: &rusqlite::Transaction, monthly_taxes: &[MonthlyTax]) -> Result<(), Error> {
+    let paid_competences = db::select_paid_competences(transaction)?;
+    let darfs = calc_darfs(monthly_taxes, &paid_competences)?;
+
+    db::delete_unpaid_darfs(transaction)?;
+    db::insert_darfs(transaction, &darfs)
+}
+
+fn calc_darfs(monthly_taxes: &[MonthlyTax], paid_competences: &[String]) -> Result<Vec<Darf>, Error> {
+    let mut darfs = Vec::new();
+    let mut carried_amount = 0.0;
+
+    for monthly_tax in monthly_taxes {
+        if paid_competences.contains(&monthly_tax.month) {
+            carried_amount = 0.0;
+            continue;
+        }
+
+        let total_amount = monthly_tax.tax_due + carried_amount;
+        if total_amount <= 0.0 {
+            continue;
+        }
+
+        let status = if total_amount < MINIMUM_DARF_AMOUNT { DarfStatus::Carried } else { DarfStatus::Pending };
+
+        darfs.push(Darf {
+            id: 0,
+            competence: monthly_tax.month.clone(),
+            revenue_code: INDIVIDUAL_REVENUE_CODE.to_string(),
+            tax_amount: monthly_tax.tax_due,
+            carried_amount,
+            total_amount,
+            due_date: due_date(&monthly_tax.month)
+                .ok_or_else(|| Error::Parse(INVALID_COMPETENCE, monthly_tax.month.clone()))?,
+            status,
+            payment_date: None,
+            paid_amount: None,
+        });
+
+        carried_amount = if status == DarfStatus::Carried { total_amount } else { 0.0 };
+    }
+
+    Ok(darfs)
+}
+
+// Last business day of the month following the competence, None if it isn't a "YYYY-MM" month
+pub(crate) fn due_date(competence: &str) -> Option<NaiveDate> {
+    let (year, month) = tax::parse_month(competence)?;
+    let (year, month) = if month > 10 { (year + 1, month - 10) } else { (year, month + 2) };
+
+    let mut date = NaiveDate::from_ymd_opt(year, month, 1)? - Duration::days(1);
+    while !is_business_day(&date) {
+        date = date - Duration::days(1);
+    }
+
+    Some(date)
+}
+
+pub(crate) fn is_business_day(date: &NaiveDate) -> bool {
+    if date.weekday() == Weekday::Sat || date.weekday() == Weekday::Sun {
+        return false;
+    }
+
+    // December 31st has no bank clearing
+    let fixed_holidays = [(1, 1), (4, 21), (5, 1), (9, 7), (10, 12), (11, 2), (11, 15), (12, 25), (12, 31)];
+    if fixed_holidays.contains(&(date.month(), date.day())) {
+        return false;
+    }
+
+    // Carnival Monday and Tuesday, Good Friday and Corpus Christi move with Easter
+    let easter = easter(date.year());
+    let movable_holidays = [-48, -47, -2, 60];
+
+    !movable_holidays.iter().any(|days| *date == easter + Duration::days(*days))
+}
+
+// Anonymous Gregorian algorithm
+fn easter(year: i32) -> NaiveDate {
+    let a = year % 19;
+    let b = year / 100;
+    let c = year % 100;
+    let d = b / 4;
+    let e = b % 4;
+    let f = (b + 8) / 25;
+    let g = (b - f + 1) / 3;
+    let h = (19 * a + b - d - g + 15) % 30;
+    let i = c / 4;
+    let k = c % 4;
+    let l = (32 + 2 * e + 2 * i - h - k) % 7;
+    let m = (a + 11 * h + 22 * l) / 451;
+    let month = (h + l - 7 * m + 114) / 31;
+    let day = (h + l - 7 * m + 114) % 31 + 1;
+
+    NaiveDate::from_ymd(year, month as u32, day as u32)
+}
+
+// "01/03/2021" as exported by the Central Bank, "03/2021" or "2021-03"
+fn parse_selic_month(value: &str) -> Option<String> {
+    if let Some(date) = parse_date(value) {
+        return Some(date.format("%Y-%m").to_string());
+    }
+
+    let (year, month) = match value.split_once('/') {
+        Some((month, year)) => (year, month),
+        None => value.split_once('-')?,
+    };
+
+    match (year.trim().parse::<i32>(), month.trim().parse::<u32>()) {
+        (Ok(year), Ok(month)) if (1..=12).contains(&month) => Some(format!("{:04}-{:02}", year, month)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn monthly_tax(month: &str, tax_due: f64) -> MonthlyTax {
+        MonthlyTax {
+            month: month.to_string(),
+            tax_due,
+            ..MonthlyTax::default()
+        }
+    }
+
+    #[test]
+    fn easter_sundays() {
+        assert_eq!(easter(2021), NaiveDate::from_ymd(2021, 4, 4));
+        assert_eq!(easter(2022), NaiveDate::from_ymd(2022, 4, 17));
+        assert_eq!(easter(2024), NaiveDate::from_ymd(2024, 3, 31));
+    }
+
+    #[test]
+    fn due_date_is_last_business_day_of_following_month() {
+        assert_eq!(due_date("2021-01"), Some(NaiveDate::from_ymd(2021, 2, 26)));
+        assert_eq!(due_date("2021-03"), Some(NaiveDate::from_ymd(2021, 4, 30)));
+        assert_eq!(due_date("2021-12"), Some(NaiveDate::from_ymd(2022, 1, 31)));
+    }
+
+    #[test]
+    fn due_date_skips_holidays() {
+        // December 31st and Good Friday (March 29th, 2024)
+        assert_eq!(due_date("2021-11"), Some(NaiveDate::from_ymd(2021, 12, 30)));
+        assert_eq!(due_date("2024-02"), Some(NaiveDate::from_ymd(2024, 3, 28)));
+    }
+
+    #[test]
+    fn due_date_skips_movable_holidays() {
+        // Carnival Monday, February 28th, 2022
+        assert_eq!(due_date("2022-01"), Some(NaiveDate::from_ymd(2022, 2, 25)));
+        // Carnival Tuesday, February 28th, 2017
+        assert_eq!(due_date("2017-01"), Some(NaiveDate::from_ymd(2017, 2, 24)));
+        // Corpus Christi, May 31st, 2018
+        assert_eq!(due_date("2018-04"), Some(NaiveDate::from_ymd(2018, 5, 30)));
+    }
+
+    #[test]
+    fn due_date_rejects_invalid_competences() {
+        assert_eq!(due_date("2021-13"), None);
+        assert_eq!(due_date("2021-00"), None);
+        assert_eq!(due_date("05/2021"), None);
+        assert_eq!(due_date("2021-1"), None);
+    }
+
+    #[test]
+    fn amounts_under_minimum_are_carried_forward() {
+        let monthly_taxes = [
+            monthly_tax("2021-01", 4.0),
+            monthly_tax("2021-02", 0.0),
+            monthly_tax("2021-03", 5.0),
+            monthly_tax("2021-04", 3.0),
+            monthly_tax("2021-05", 2.0),
+        ];
+
+        let darfs = calc_darfs(&monthly_taxes, &[]).unwrap();
+
+        let summary: Vec<(&str, DarfStatus, f64, f64)> = darfs.iter()
+            .map(|darf| (darf.competence.as_str(), darf.status, darf.carried_amount, darf.total_amount))
+            .collect();
+        assert_eq!(summary, vec![
+            ("2021-01", DarfStatus::Carried, 0.0, 4.0),
+            ("2021-02", DarfStatus::Carried, 4.0, 4.0),
+            ("2021-03", DarfStatus::Carried, 4.0, 9.0),
+            ("2021-04", DarfStatus::Pending, 9.0, 12.0),
+            ("2021-05", DarfStatus::Carried, 0.0, 2.0),
+        ]);
+    }
+
+    #[test]
+    fn paid_competences_are_kept_and_reset_carried_amount() {
+        let monthly_taxes = [
+            monthly_tax("2021-01", 4.0),
+            monthly_tax("2021-02", 6.0),
+            monthly_tax("2021-03", 8.0),
+        ];
+
+        let darfs = calc_darfs(&monthly_taxes, &["2021-02".to_string()]).unwrap();
+
+        assert_eq!(darfs.len(), 2);
+        assert_eq!(darfs[0].competence, "2021-01");
+        assert_eq!((darfs[1].competence.as_str(), darfs[1].carried_amount, darfs[1].status), ("2021-03", 0.0, DarfStatus::Carried));
+    }
+
+    #[test]
+    fn selic_months() {
+        assert_eq!(parse_selic_month("01/03/2021"), Some("2021-03".to_string()));
+        assert_eq!(parse_selic_month("03/2021"), Some("2021-03".to_string()));
+        assert_eq!(parse_selic_month("2021-03"), Some("2021-03".to_string()));
+        assert_eq!(parse_selic_month("13/2021"), None);
+    }
+}
@@ -0,0 +1,173 @@
+use chrono::NaiveDate;
+use rusqlite::named_params;
+
+use crate::commons::{QueryMapper, ToVec};
+use crate::darf::types::{Darf, DarfStatus, SelicRate};
+use crate::error::Error;
+
+const DARF_MAPPER: QueryMapper<Darf> = |row| {
+    Ok(Darf {
+        id: row.get(0)?,
+        competence: row.get(1)?,
+        revenue_code: row.get(2)?,
+        tax_amount: row.get(3)?,
+        carried_amount: row.get(4)?,
+        total_amount: row.get(5)?,
+        due_date: row.get(6)?,
+        status: row.get(7)?,
+        payment_date: row.get(8)?,
+        paid_amount: row.get(9)?,
+    })
+};
+
+pub(crate) fn select_darfs_by_status(connection: &rusqlite::Connection, status: &DarfStatus) -> Result<Vec<Darf>, Error> {
+    let mut statement = connection.prepare_cached(
+    "SELECT
+            id,
+            competencia,
+            codigo_receita,
+            valor_imposto,
+            valor_acumulado,
+            valor_total,
+            vencimento,
+            situacao,
+            data_pagamento,
+            valor_pago
+        FROM darf
+        WHERE situacao = :status
+        ORDER BY competencia ASC"
+    )?;
+
+    let darf_rows = statement.query_map(named_params! { ":status": status }, DARF_MAPPER)?;
+
+    Ok(darf_rows.to_vec())
+}
+
+pub(crate) fn select_darfs_by_year(connection: &rusqlite::Connection, year: &i32) -> Result<Vec<Darf>, Error> {
+    let mut statement = connection.prepare_cached(
+    "SELECT
+            id,
+            competencia,
+            codigo_receita,
+            valor_imposto,
+            valor_acumulado,
+            valor_total,
+            vencimento,
+            situacao,
+            data_pagamento,
+            valor_pago
+        FROM darf
+        WHERE SUBSTR(competencia, 1, 4) = :year
+        ORDER BY competencia ASC"
+    )?;
+
+    let darf_rows = statement.query_map(named_params! { ":year": year.to_string() }, DARF_MAPPER)?;
+
+    Ok(darf_rows.to_vec())
+}
+
+pub(crate) fn select_paid_competences(connection: &rusqlite::Connection) -> Result<Vec<String>, Error> {
+    let mut statement = connection.prepare_cached("SELECT competencia FROM darf WHERE situacao = :status")?;
+
+    let competence_rows = statement.query_map(named_params! { ":status": DarfStatus::Paid }, |row| row.get(0))?;
+
+    competence_rows
+        .collect::<rusqlite::Result<Vec<String>>>()
+        .map_err(Error::from)
+}
+
+pub(crate) fn delete_unpaid_darfs(transaction: &rusqlite::Transaction) -> Result<(), Error> {
+    transaction.execute("DELETE FROM darf WHERE situacao <> :status", named_params! { ":status": DarfStatus::Paid })?;
+
+    Ok(())
+}
+
+pub(crate) fn insert_darfs(transaction: &rusqlite::Transaction, darfs: &Vec<Darf>) -> Result<(), Error> {
+    let mut statement = transaction.prepare_cached(
+        "INSERT INTO darf (
+            competencia,
+            codigo_receita,
+            valor_imposto,
+            valor_acumulado,
+            valor_total,
+            vencimento,
+            situacao
+        ) VALUES (
+            :competence,
+            :revenue_code,
+            :tax_amount,
+            :carried_amount,
+            :total_amount,
+            :due_date,
+            :status
+        )"
+    )?;
+
+    for darf in darfs {
+        statement.insert(named_params! {
+            ":competence": darf.competence,
+            ":revenue_code": darf.revenue_code,
+            ":tax_amount": darf.tax_amount,
+            ":carried_amount": darf.carried_amount,
+            ":total_amount": darf.total_amount,
+            ":due_date": darf.due_date,
+            ":status": darf.status
+        })?;
+    }
+
+    Ok(())
+}
+
+pub(crate) fn update_darf_payment(connection: &rusqlite::Connection, darf_id: &i64, payment_date: &NaiveDate, paid_amount: &f64) -> Result<usize, Error> {
+    let mut statement = connection.prepare_cached(
+    "UPDATE darf SET
+            situacao = :status,
+            data_pagamento = :payment_date,
+            valor_pago = :paid_amount
+        WHERE id = :id AND situacao = :pending_status"
+    )?;
+
+    statement.execute(named_params! {
+        ":status": DarfStatus::Paid,
+        ":payment_date": payment_date,
+        ":paid_amount": paid_amount,
+        ":id": darf_id,
+        ":pending_status": DarfStatus::Pending
+    }).map_err(Error::from)
+}
+
+pub(crate) fn select_selic_rates_between(connection: &rusqlite::Connection, first_month: &str, last_month: &str) -> Result<Vec<SelicRate>, Error> {
+    let mut statement = connection.prepare_cached(
+    "SELECT
+            mes,
+            taxa
+        FROM taxa_selic
+        WHERE mes BETWEEN :first_month AND :last_month
+        ORDER BY mes ASC"
+    )?;
+
+    let selic_rows = statement.query_map(
+        named_params! {
+            ":first_month": first_month,
+            ":last_month": last_month
+        },
+        |row| Ok(SelicRate { month: row.get(0)?, rate: row.get(1)? })
+    )?;
+
+    selic_rows
+        .collect::<rusqlite::Result<Vec<SelicRate>>>()
+        .map_err(Error::from)
+}
+
+pub(crate) fn insert_or_replace_selic_rates(transaction: &rusqlite::Transaction, selic_rates: &Vec<SelicRate>) -> Result<(), Error> {
+    let mut statement = transaction.prepare_cached("INSERT OR REPLACE INTO taxa_selic (mes, taxa) VALUES (:month, :rate)")?;
+
+    for selic_rate in selic_rates {
+        statement.execute(named_params! {
+            ":month": selic_rate.month,
+            ":rate": selic_rate.rate
+        })?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,66 @@
+use chrono::NaiveDate;
+use rusqlite::types::{FromSql, FromSqlError, FromSqlResult, ToSql, ToSqlOutput, ValueRef};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub(crate) enum DarfStatus {
+    Pending,
+    Carried,
+    Paid,
+}
+
+impl ToSql for DarfStatus {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(ToSqlOutput::from(match self {
+            DarfStatus::Pending => "PENDING",
+            DarfStatus::Carried => "CARRIED",
+            DarfStatus::Paid => "PAID",
+        }))
+    }
+}
+
+impl FromSql for DarfStatus {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        match value.as_str()? {
+            "PENDING" => Ok(DarfStatus::Pending),
+            "CARRIED" => Ok(DarfStatus::Carried),
+            "PAID" => Ok(DarfStatus::Paid),
+            _ => Err(FromSqlError::InvalidType),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub(crate) struct Darf {
+    #[serde(default)]
+    pub(crate) id: i64,
+    pub(crate) competence: String,
+    pub(crate) revenue_code: String,
+    pub(crate) tax_amount: f64,
+    pub(crate) carried_amount: f64,
+    pub(crate) total_amount: f64,
+    pub(crate) due_date: NaiveDate,
+    pub(crate) status: DarfStatus,
+    pub(crate) payment_date: Option<NaiveDate>,
+    pub(crate) paid_amount: Option<f64>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub(crate) struct SelicRate {
+    pub(crate) month: String,
+    pub(crate) rate: f64,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub(crate) struct LateDarf {
+    pub(crate) principal: f64,
+    pub(crate) due_date: NaiveDate,
+    pub(crate) payment_date: NaiveDate,
+    pub(crate) days_late: i64,
+    pub(crate) fine_rate: f64,
+    pub(crate) fine: f64,
+    pub(crate) interest_rate: f64,
+    pub(crate) interest: f64,
+    pub(crate) total: f64,
+}
@@ -1,71 +1,207 @@
+use std::ops::{Deref, DerefMut};
+use std::sync::{Mutex, MutexGuard};
+use std::time::Duration;
+
 use rusqlite;
-use tauri::InvokeError;
+use serde::{Deserialize, Serialize};
+use tauri::{InvokeError, State};
 
 use crate::config;
+use crate::error::Error;
+use crate::database::migrations::MIGRATIONS;
+use crate::position;
+
+mod migrations;
 
 const CANT_OPEN_DB_FILE: &str = "CANT_OPEN_DB_FILE";
-const CANT_CREATE_DB_TABLES: &str = "CANT_CREATE_DB_TABLES";
+const CANT_MIGRATE_DATABASE: &str = "CANT_MIGRATE_DATABASE";
+const DATABASE_NEWER_THAN_APP: &str = "DATABASE_NEWER_THAN_APP";
+const BUSY_TIMEOUT_SECONDS: u64 = 5;
+const STATEMENT_CACHE_CAPACITY: usize = 64;
+
+#[derive(Serialize, Deserialize, Debug)]
+pub(crate) struct OrphanRow {
+    pub(crate) table: String,
+    pub(crate) row_id: Option<i64>,
+    pub(crate) parent_table: String,
+}
+
+// Single connection shared by every command through Tauri managed state. Opening errors are
+// reported once at startup and kept; the connection is only retried when the first-run setup
+// asks for it after writing the config file.
+pub(crate) struct Database {
+    connection: Mutex<Result<rusqlite::Connection, &'static str>>,
+}
+
+pub(crate) struct ConnectionGuard<'a>(MutexGuard<'a, Result<rusqlite::Connection, &'static str>>);
+
+impl Database {
+    pub(crate) fn open() -> Self {
+        let connection = open_connection()
+            .map_err(|error| {
+                println!("Erro ao abrir banco de dados na inicialização: {}", error);
+                error.code()
+            });
+
+        Database {
+            connection: Mutex::new(connection),
+        }
+    }
+
+    pub(crate) fn connection(&self) -> Result<ConnectionGuard<'_>, Error> {
+        let connection = self.lock();
+
+        if let Err(code) = connection.as_ref() {
+            return Err(Error::Config(code));
+        }
+
+        Ok(ConnectionGuard(connection))
+    }
+
+    pub(crate) fn reopen(&self) -> Result<ConnectionGuard<'_>, Error> {
+        let mut connection = self.lock();
+
+        match open_connection() {
+            Ok(new_connection) => *connection = Ok(new_connection),
+            Err(error) => {
+                *connection = Err(error.code());
+                return Err(error);
+            }
+        }
 
+        Ok(ConnectionGuard(connection))
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Result<rusqlite::Connection, &'static str>> {
+        // A panic while holding the lock leaves the connection itself usable
+        self.connection.lock().unwrap_or_else(|error| error.into_inner())
+    }
+}
+
+impl Deref for ConnectionGuard<'_> {
+    type Target = rusqlite::Connection;
+
+    fn deref(&self) -> &Self::Target {
+        self.0.as_ref().expect("conexão aberta em Database::connection")
+    }
+}
+
+impl DerefMut for ConnectionGuard<'_> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.0.as_mut().expect("conexão aberta em Database::connection")
+    }
+}
+
+// Called by the frontend once the first-run setup has written the config file. Opening the
+// connection is what applies the pending migrations, so existing databases are brought up to
+// date at startup as well. Orphan rows found afterwards are returned for the setup screen.
 #[tauri::command(async)]
-pub(crate) fn create_database() -> Result<(), InvokeError> {
-    let connection = get_connection()?;
-
-    connection.execute("CREATE TABLE IF NOT EXISTS corretora (
-        id INTEGER PRIMARY KEY,
-        nome TEXT UNIQUE NOT NULL
-    )", []).map_err(|error| {
-        println!("{}", error);
-        InvokeError::from(&CANT_CREATE_DB_TABLES)
-    })?;
+pub(crate) fn create_database(database: State<'_, Database>) -> Result<Vec<OrphanRow>, InvokeError> {
+    let connection = database.reopen()?;
 
-    connection.execute("CREATE TABLE IF NOT EXISTS nota_corretagem (
-        id INTEGER PRIMARY KEY,
-        corretora_id INTEGER NOT NULL,
-        total_taxa_liquidacao REAL NOT NULL,
-        total_emolumentos REAL NOT NULL,
-        total_corretagem REAL NOT NULL,
-        total_iss REAL NOT NULL,
-        total_irrf REAL NOT NULL,
-        total_custo REAL NOT NULL,
-        total_transacionado REAL NOT NULL,
-        total_comprado REAL NOT NULL,
-        total_vendido REAL NOT NULL,
-        data_pregao TEXT NOT NULL,
-        FOREIGN KEY(corretora_id) REFERENCES corretora(id)
-    )", []).map_err(|error| {
-        println!("{}", error);
-        InvokeError::from(&CANT_CREATE_DB_TABLES)
-    })?;
+    Ok(select_orphan_rows(&connection)?)
+}
+
+// Rows left behind while foreign keys were not enforced
+#[tauri::command(async)]
+pub(crate) fn get_orphan_rows(database: State<'_, Database>) -> Result<Vec<OrphanRow>, InvokeError> {
+    let connection = database.connection()?;
 
-    connection.execute("CREATE TABLE IF NOT EXISTS nota_corretagem_ordem (
-        id INTEGER PRIMARY KEY,
-        nota_corretagem_id INTEGER NOT NULL,
-        tipo TEXT NOT NULL,
-        papel TEXT NOT NULL,
-        quantidade INTEGER NOT NULL,
-        valor_ordem REAL NOT NULL,
-        valor_unidade REAL NOT NULL,
-        taxa_liquidacao REAL NOT NULL,
-        emolumentos REAL NOT NULL,
-        corretagem REAL NOT NULL,
-        iss REAL NOT NULL,
-        irrf REAL NOT NULL,
-        total_custo REAL NOT NULL,
-        FOREIGN KEY(nota_corretagem_id) REFERENCES nota_corretagem(id)
-    )", []).map_err(|error| {
-        println!("{}", error);
-        InvokeError::from(&CANT_CREATE_DB_TABLES)
+    Ok(select_orphan_rows(&connection)?)
+}
+
+// Pending migrations are applied one transaction each, so a failure leaves the database at the
+// last successful version. Returns whether any was applied.
+fn migrate(connection: &mut rusqlite::Connection) -> Result<bool, Error> {
+    let version = schema_version(connection)?;
+
+    for (index, migration) in MIGRATIONS.iter().enumerate().skip(version as usize) {
+        let transaction = connection.transaction()?;
+
+        transaction.execute_batch(migration)
+            .and_then(|_| transaction.pragma_update(None, "user_version", &(index as i64 + 1)))
+            .map_err(|error| {
+                println!("Erro ao aplicar migração {}: {}", index + 1, error);
+                Error::Config(CANT_MIGRATE_DATABASE)
+            })?;
+
+        transaction.commit()?;
+    }
+
+    Ok((version as usize) < MIGRATIONS.len())
+}
+
+fn set_foreign_keys(connection: &rusqlite::Connection, enabled: bool) -> Result<(), Error> {
+    Ok(connection.pragma_update(None, "foreign_keys", &enabled)?)
+}
+
+fn select_orphan_rows(connection: &rusqlite::Connection) -> Result<Vec<OrphanRow>, Error> {
+    let mut statement = connection.prepare("PRAGMA foreign_key_check")?;
+
+    let orphan_rows = statement.query_map([], |row| {
+        Ok(OrphanRow {
+            table: row.get(0)?,
+            row_id: row.get(1)?,
+            parent_table: row.get(2)?,
+        })
     })?;
 
-    Ok(())
+    Ok(orphan_rows.collect::<rusqlite::Result<Vec<OrphanRow>>>()?)
 }
 
-pub(crate) fn get_connection() -> Result<rusqlite::Connection, InvokeError> {
+fn open_connection() -> Result<rusqlite::Connection, Error> {
     let app_config = config::get_config()?;
 
-    rusqlite::Connection::open(app_config.db_file)
+    let mut connection = rusqlite::Connection::open(app_config.db_file)
         .map_err(|error| {
             println!("Erro ao abrir banco de dados: {}", error);
-            InvokeError::from(&CANT_OPEN_DB_FILE)
-        })
+            Error::Config(CANT_OPEN_DB_FILE)
+        })?;
+
+    connection.execute_batch("PRAGMA journal_mode = WAL;")
+        .and_then(|_| connection.busy_timeout(Duration::from_secs(BUSY_TIMEOUT_SECONDS)))
+        .map_err(|error| {
+            println!("Erro ao configurar banco de dados: {}", error);
+            Error::Config(CANT_OPEN_DB_FILE)
+        })?;
+
+    connection.set_prepared_statement_cache_capacity(STATEMENT_CACHE_CAPACITY);
+
+    if schema_version(&connection)? > MIGRATIONS.len() as i64 {
+        println!("Banco de dados criado por uma versão mais nova da aplicação");
+        return Err(Error::Config(DATABASE_NEWER_THAN_APP));
+    }
+
+    // Some migrations rebuild tables, which is only safe with foreign keys off
+    set_foreign_keys(&connection, false)?;
+    let migration_result = migrate(&mut connection);
+    set_foreign_keys(&connection, true)?;
+
+    // Migrations may change the orders the realized results and taxes are derived from
+    if migration_result? {
+        let transaction = connection.transaction()?;
+        position::update_realized_results(&transaction)?;
+        transaction.commit()?;
+    }
+
+    for orphan_row in select_orphan_rows(&connection)? {
+        println!("Registro órfão na tabela {} (id {:?}) sem correspondente em {}", orphan_row.table, orphan_row.row_id, orphan_row.parent_table);
+    }
+
+    Ok(connection)
+}
+
+fn schema_version(connection: &rusqlite::Connection) -> Result<i64, Error> {
+    Ok(connection.pragma_query_value(None, "user_version", |row| row.get(0))?)
+}
+
+// Fresh database with every migration applied, for the tests of the SQL in each module
+#[cfg(test)]
+pub(crate) fn open_in_memory() -> rusqlite::Connection {
+    let mut connection = rusqlite::Connection::open_in_memory().unwrap();
+
+    migrate(&mut connection).unwrap();
+    set_foreign_keys(&connection, true).unwrap();
+
+    connection
 }
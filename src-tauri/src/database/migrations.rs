@@ -0,0 +1,320 @@
+// Migrations run in order and each one bumps PRAGMA user_version to its position in the list,
+// so a migration must never be edited or removed once released; add a new one instead.
+pub(crate) const MIGRATIONS: &[&str] = &[
+    V1_INITIAL_SCHEMA,
+    V2_CASCADE_DELETES,
+    V3_RECOMPUTE_ORDER_FEES,
+    V4_MANUAL_ASSET_CLASS,
+    V5_INSTRUMENT_TRADING_NAME,
+    V6_BROKERAGE_NOTE_NUMBER,
+    V7_CORPORATE_EVENT_RESULTS,
+];
+
+// Tables that existed before versioning are created only when missing
+const V1_INITIAL_SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS corretora (
+        id INTEGER PRIMARY KEY,
+        nome TEXT UNIQUE NOT NULL
+    );
+
+    CREATE TABLE IF NOT EXISTS nota_corretagem (
+        id INTEGER PRIMARY KEY,
+        corretora_id INTEGER NOT NULL,
+        total_taxa_liquidacao REAL NOT NULL,
+        total_emolumentos REAL NOT NULL,
+        total_corretagem REAL NOT NULL,
+        total_iss REAL NOT NULL,
+        total_irrf REAL NOT NULL,
+        total_custo REAL NOT NULL,
+        total_transacionado REAL NOT NULL,
+        total_comprado REAL NOT NULL,
+        total_vendido REAL NOT NULL,
+        data_pregao TEXT NOT NULL,
+        FOREIGN KEY(corretora_id) REFERENCES corretora(id)
+    );
+
+    CREATE TABLE IF NOT EXISTS nota_corretagem_ordem (
+        id INTEGER PRIMARY KEY,
+        nota_corretagem_id INTEGER NOT NULL,
+        tipo TEXT NOT NULL,
+        papel TEXT NOT NULL,
+        quantidade INTEGER NOT NULL,
+        valor_ordem REAL NOT NULL,
+        valor_unidade REAL NOT NULL,
+        taxa_liquidacao REAL NOT NULL,
+        emolumentos REAL NOT NULL,
+        corretagem REAL NOT NULL,
+        iss REAL NOT NULL,
+        irrf REAL NOT NULL,
+        total_custo REAL NOT NULL,
+        FOREIGN KEY(nota_corretagem_id) REFERENCES nota_corretagem(id)
+    );
+
+    CREATE TABLE IF NOT EXISTS resultado_operacao (
+        id INTEGER PRIMARY KEY,
+        nota_corretagem_ordem_id INTEGER NOT NULL,
+        corretora_id INTEGER NOT NULL,
+        data_pregao TEXT NOT NULL,
+        papel TEXT NOT NULL,
+        quantidade INTEGER NOT NULL,
+        preco_medio REAL NOT NULL,
+        custo_aquisicao REAL NOT NULL,
+        valor_venda REAL NOT NULL,
+        resultado REAL NOT NULL,
+        FOREIGN KEY(nota_corretagem_ordem_id) REFERENCES nota_corretagem_ordem(id),
+        FOREIGN KEY(corretora_id) REFERENCES corretora(id)
+    );
+
+    CREATE TABLE IF NOT EXISTS resultado_day_trade (
+        id INTEGER PRIMARY KEY,
+        corretora_id INTEGER NOT NULL,
+        data_pregao TEXT NOT NULL,
+        papel TEXT NOT NULL,
+        quantidade INTEGER NOT NULL,
+        preco_medio_compra REAL NOT NULL,
+        preco_medio_venda REAL NOT NULL,
+        custo_aquisicao REAL NOT NULL,
+        valor_venda REAL NOT NULL,
+        resultado REAL NOT NULL,
+        FOREIGN KEY(corretora_id) REFERENCES corretora(id)
+    );
+
+    CREATE TABLE IF NOT EXISTS prejuizo_acumulado (
+        id INTEGER PRIMARY KEY,
+        mes TEXT NOT NULL,
+        categoria TEXT NOT NULL,
+        disponivel REAL NOT NULL,
+        incorrido REAL NOT NULL,
+        consumido REAL NOT NULL,
+        remanescente REAL NOT NULL
+    );
+
+    CREATE TABLE IF NOT EXISTS darf (
+        id INTEGER PRIMARY KEY,
+        competencia TEXT UNIQUE NOT NULL,
+        codigo_receita TEXT NOT NULL,
+        valor_imposto REAL NOT NULL,
+        valor_acumulado REAL NOT NULL,
+        valor_total REAL NOT NULL,
+        vencimento TEXT NOT NULL,
+        situacao TEXT NOT NULL,
+        data_pagamento TEXT,
+        valor_pago REAL
+    );
+
+    CREATE TABLE IF NOT EXISTS ativo (
+        id INTEGER PRIMARY KEY,
+        papel TEXT UNIQUE NOT NULL,
+        classe TEXT NOT NULL,
+        nome_emissor TEXT,
+        cnpj TEXT,
+        isin TEXT
+    );
+
+    CREATE TABLE IF NOT EXISTS evento_corporativo (
+        id INTEGER PRIMARY KEY,
+        papel TEXT NOT NULL,
+        tipo TEXT NOT NULL,
+        data_ex TEXT NOT NULL,
+        proporcao_origem REAL NOT NULL,
+        proporcao_destino REAL NOT NULL,
+        custo_unitario REAL,
+        valor_dinheiro REAL,
+        quantidade INTEGER
+    );
+
+    CREATE TABLE IF NOT EXISTS evento_corporativo_destino (
+        id INTEGER PRIMARY KEY,
+        evento_corporativo_id INTEGER NOT NULL,
+        papel TEXT NOT NULL,
+        proporcao_origem REAL NOT NULL,
+        proporcao_destino REAL NOT NULL,
+        percentual_custo REAL NOT NULL,
+        FOREIGN KEY(evento_corporativo_id) REFERENCES evento_corporativo(id)
+    );
+
+    CREATE TABLE IF NOT EXISTS provento (
+        id INTEGER PRIMARY KEY,
+        corretora_id INTEGER NOT NULL,
+        papel TEXT NOT NULL,
+        tipo TEXT NOT NULL,
+        data_ex TEXT NOT NULL,
+        data_pagamento TEXT NOT NULL,
+        valor_bruto REAL NOT NULL,
+        imposto_retido REAL NOT NULL,
+        valor_liquido REAL NOT NULL,
+        FOREIGN KEY(corretora_id) REFERENCES corretora(id)
+    );
+
+    CREATE TABLE IF NOT EXISTS instrumento (
+        papel TEXT PRIMARY KEY,
+        isin TEXT,
+        nome_emissor TEXT,
+        cnpj TEXT,
+        classe TEXT NOT NULL,
+        preco_exercicio REAL,
+        vencimento TEXT
+    );
+
+    CREATE TABLE IF NOT EXISTS taxa_selic (
+        mes TEXT PRIMARY KEY,
+        taxa REAL NOT NULL
+    );
+";
+
+// SQLite cannot alter a foreign key, so the child tables are rebuilt. Runs with foreign keys
+// off; orphan rows are copied as they are and reported by the caller.
+const V2_CASCADE_DELETES: &str = "
+    CREATE TABLE nota_corretagem_ordem_nova (
+        id INTEGER PRIMARY KEY,
+        nota_corretagem_id INTEGER NOT NULL,
+        tipo TEXT NOT NULL,
+        papel TEXT NOT NULL,
+        quantidade INTEGER NOT NULL,
+        valor_ordem REAL NOT NULL,
+        valor_unidade REAL NOT NULL,
+        taxa_liquidacao REAL NOT NULL,
+        emolumentos REAL NOT NULL,
+        corretagem REAL NOT NULL,
+        iss REAL NOT NULL,
+        irrf REAL NOT NULL,
+        total_custo REAL NOT NULL,
+        FOREIGN KEY(nota_corretagem_id) REFERENCES nota_corretagem(id) ON DELETE CASCADE
+    );
+
+    INSERT INTO nota_corretagem_ordem_nova SELECT * FROM nota_corretagem_ordem;
+    DROP TABLE nota_corretagem_ordem;
+    ALTER TABLE nota_corretagem_ordem_nova RENAME TO nota_corretagem_ordem;
+
+    CREATE TABLE resultado_operacao_nova (
+        id INTEGER PRIMARY KEY,
+        nota_corretagem_ordem_id INTEGER NOT NULL,
+        corretora_id INTEGER NOT NULL,
+        data_pregao TEXT NOT NULL,
+        papel TEXT NOT NULL,
+        quantidade INTEGER NOT NULL,
+        preco_medio REAL NOT NULL,
+        custo_aquisicao REAL NOT NULL,
+        valor_venda REAL NOT NULL,
+        resultado REAL NOT NULL,
+        FOREIGN KEY(nota_corretagem_ordem_id) REFERENCES nota_corretagem_ordem(id) ON DELETE CASCADE,
+        FOREIGN KEY(corretora_id) REFERENCES corretora(id)
+    );
+
+    INSERT INTO resultado_operacao_nova SELECT * FROM resultado_operacao;
+    DROP TABLE resultado_operacao;
+    ALTER TABLE resultado_operacao_nova RENAME TO resultado_operacao;
+
+    CREATE TABLE evento_corporativo_destino_nova (
+        id INTEGER PRIMARY KEY,
+        evento_corporativo_id INTEGER NOT NULL,
+        papel TEXT NOT NULL,
+        proporcao_origem REAL NOT NULL,
+        proporcao_destino REAL NOT NULL,
+        percentual_custo REAL NOT NULL,
+        FOREIGN KEY(evento_corporativo_id) REFERENCES evento_corporativo(id) ON DELETE CASCADE
+    );
+
+    INSERT INTO evento_corporativo_destino_nova SELECT * FROM evento_corporativo_destino;
+    DROP TABLE evento_corporativo_destino;
+    ALTER TABLE evento_corporativo_destino_nova RENAME TO evento_corporativo_destino;
+";
+
+// Settlement and emolument fees used to be divided by the order value instead of allocated in
+// proportion to it, so orders saved before the fix carry a wrong total cost.
+const V3_RECOMPUTE_ORDER_FEES: &str = "
+    UPDATE nota_corretagem_ordem SET
+        taxa_liquidacao = (
+            SELECT CASE WHEN nc.total_transacionado != 0
+                THEN nc.total_taxa_liquidacao / nc.total_transacionado * nota_corretagem_ordem.valor_ordem
+                ELSE 0 END
+            FROM nota_corretagem nc
+            WHERE nc.id = nota_corretagem_ordem.nota_corretagem_id
+        ),
+        emolumentos = (
+            SELECT CASE WHEN nc.total_transacionado != 0
+                THEN nc.total_emolumentos / nc.total_transacionado * nota_corretagem_ordem.valor_ordem
+                ELSE 0 END
+            FROM nota_corretagem nc
+            WHERE nc.id = nota_corretagem_ordem.nota_corretagem_id
+        );
+
+    UPDATE nota_corretagem_ordem SET
+        total_custo = taxa_liquidacao + emolumentos + corretagem + iss + irrf;
+";
+
+// Classes set by the user are kept when the instruments file is imported again
+const V4_MANUAL_ASSET_CLASS: &str = "
+    ALTER TABLE ativo ADD COLUMN classe_manual INTEGER NOT NULL DEFAULT 0;
+";
+
+// Brokerage notes name the security by its trading name and specification ("PETROBRAS PN N2")
+// instead of the ticker, so both are kept to resolve it.
+const V5_INSTRUMENT_TRADING_NAME: &str = "
+    ALTER TABLE instrumento ADD COLUMN nome_pregao TEXT;
+    ALTER TABLE instrumento ADD COLUMN especificacao TEXT;
+";
+
+// Notes imported from PDF keep their number, so the same note is not imported twice while other
+// notes from the same broker and day still are. Notes typed in or from B3 have none.
+const V6_BROKERAGE_NOTE_NUMBER: &str = "
+    ALTER TABLE nota_corretagem ADD COLUMN numero_nota TEXT;
+    CREATE UNIQUE INDEX nota_corretagem_numero_nota ON nota_corretagem (corretora_id, numero_nota)
+        WHERE numero_nota IS NOT NULL;
+";
+
+// Cash paid in a merger or spin-off above the cost basis is a gain with no order behind it
+const V7_CORPORATE_EVENT_RESULTS: &str = "
+    CREATE TABLE resultado_evento_corporativo (
+        id INTEGER PRIMARY KEY,
+        evento_corporativo_id INTEGER NOT NULL,
+        data_ex TEXT NOT NULL,
+        papel TEXT NOT NULL,
+        quantidade INTEGER NOT NULL,
+        custo_aquisicao REAL NOT NULL,
+        valor_recebido REAL NOT NULL,
+        resultado REAL NOT NULL,
+        FOREIGN KEY(evento_corporativo_id) REFERENCES evento_corporativo(id) ON DELETE CASCADE
+    );
+";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn v3_allocates_note_fees_by_order_value() {
+        let connection = rusqlite::Connection::open_in_memory().unwrap();
+        for migration in &MIGRATIONS[..2] {
+            connection.execute_batch(migration).unwrap();
+        }
+
+        connection.execute_batch("
+            INSERT INTO corretora VALUES (1, 'XP');
+            INSERT INTO nota_corretagem VALUES (1, 1, 1.0, 0.2, 5.0, 0.5, 0.0, 6.7, 5000.0, 4000.0, 1000.0, '2021-03-05');
+            INSERT INTO nota_corretagem VALUES (2, 1, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, '2021-03-08');
+            INSERT INTO nota_corretagem_ordem VALUES (1, 1, 'C', 'PETR4', 100, 4000.0, 40.0, 1.0, 0.2, 4.0, 0.4, 0.0, 5.6);
+            INSERT INTO nota_corretagem_ordem VALUES (2, 1, 'V', 'VALE3', 10, 1000.0, 100.0, 0.0, 0.0, 1.0, 0.1, 0.05, 1.15);
+            INSERT INTO nota_corretagem_ordem VALUES (3, 2, 'C', 'ITSA4', 0, 0.0, 0.0, 0.3, 0.3, 0.0, 0.0, 0.0, 0.6);
+        ").unwrap();
+
+        connection.execute_batch(MIGRATIONS[2]).unwrap();
+
+        let fees = |id: i64| -> (f64, f64, f64) {
+            connection.query_row(
+                "SELECT taxa_liquidacao, emolumentos, total_custo FROM nota_corretagem_ordem WHERE id = ?",
+                [id],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            ).unwrap()
+        };
+
+        let close = |left: (f64, f64, f64), right: (f64, f64, f64)| {
+            (left.0 - right.0).abs() < 1e-9 && (left.1 - right.1).abs() < 1e-9 && (left.2 - right.2).abs() < 1e-9
+        };
+
+        assert!(close(fees(1), (0.8, 0.16, 5.36)));
+        assert!(close(fees(2), (0.2, 0.04, 1.39)));
+
+        assert_eq!(fees(3), (0.0, 0.0, 0.0));
+    }
+}
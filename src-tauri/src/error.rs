@@ -0,0 +1,123 @@
+use std::fmt;
+use std::io;
+
+use rusqlite::ffi;
+use serde::ser::SerializeStruct;
+use serde::{Deserialize, Serialize, Serializer};
+
+const RECORD_NOT_FOUND: &str = "RECORD_NOT_FOUND";
+const VALIDATION_ERROR: &str = "VALIDATION_ERROR";
+const DATABASE_ERROR: &str = "DATABASE_ERROR";
+const UNIQUE_VIOLATION: &str = "UNIQUE_VIOLATION";
+const FOREIGN_KEY_VIOLATION: &str = "FOREIGN_KEY_VIOLATION";
+const CONSTRAINT_VIOLATION: &str = "CONSTRAINT_VIOLATION";
+
+// Field error codes shared by the validations
+pub(crate) const REQUIRED: &str = "REQUIRED";
+pub(crate) const MUST_BE_POSITIVE: &str = "MUST_BE_POSITIVE";
+pub(crate) const MUST_NOT_BE_NEGATIVE: &str = "MUST_NOT_BE_NEGATIVE";
+pub(crate) const UNKNOWN_VALUE: &str = "UNKNOWN_VALUE";
+pub(crate) const FUTURE_DATE: &str = "FUTURE_DATE";
+pub(crate) const NOT_FOUND: &str = "NOT_FOUND";
+
+// Unique columns with a code of their own, as "table.column" in the SQLite message
+const UNIQUE_CONSTRAINTS: &[(&str, &str)] = &[
+    ("corretora.nome", "BROKER_NAME_ALREADY_EXISTS"),
+    ("nota_corretagem.corretora_id, nota_corretagem.numero_nota", "BROKERAGE_NOTE_ALREADY_EXISTS"),
+];
+
+// Reaches the frontend as { code, message, details }. The code is stable and is what the UI
+// should match on; the message is only meant for logs and as a fallback.
+#[derive(Debug)]
+pub(crate) enum Error {
+    NotFound(&'static str),
+    Validation(Vec<FieldError>),
+    ConstraintViolation(&'static str),
+    Io(&'static str, io::Error),
+    Config(&'static str),
+    Parse(&'static str, String),
+    Database(rusqlite::Error),
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub(crate) struct FieldError {
+    pub(crate) field: String,
+    pub(crate) code: String,
+}
+
+impl Error {
+    pub(crate) fn code(&self) -> &'static str {
+        match self {
+            Error::NotFound(code) => code,
+            Error::Validation(_) => VALIDATION_ERROR,
+            Error::ConstraintViolation(code) => code,
+            Error::Io(code, _) => code,
+            Error::Config(code) => code,
+            Error::Parse(code, _) => code,
+            Error::Database(_) => DATABASE_ERROR,
+        }
+    }
+}
+
+impl FieldError {
+    pub(crate) fn new(field: &str, code: &str) -> Self {
+        FieldError {
+            field: field.to_string(),
+            code: code.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::NotFound(_) => write!(formatter, "Registro não encontrado"),
+            Error::Validation(errors) => write!(formatter, "{} campo(s) inválido(s)", errors.len()),
+            Error::ConstraintViolation(_) => write!(formatter, "Operação viola uma restrição do banco de dados"),
+            Error::Io(_, error) => write!(formatter, "Erro de leitura ou escrita: {}", error),
+            Error::Config(_) => write!(formatter, "Configuração inválida"),
+            Error::Parse(_, message) => write!(formatter, "Erro ao interpretar dados: {}", message),
+            Error::Database(error) => write!(formatter, "Erro no banco de dados: {}", error),
+        }
+    }
+}
+
+impl Serialize for Error {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let details = match self {
+            Error::Validation(errors) => Some(errors),
+            _ => None,
+        };
+
+        let mut state = serializer.serialize_struct("Error", 3)?;
+        state.serialize_field("code", self.code())?;
+        state.serialize_field("message", &self.to_string())?;
+        state.serialize_field("details", &details)?;
+        state.end()
+    }
+}
+
+impl From<rusqlite::Error> for Error {
+    fn from(error: rusqlite::Error) -> Self {
+        match &error {
+            rusqlite::Error::QueryReturnedNoRows => Error::NotFound(RECORD_NOT_FOUND),
+            rusqlite::Error::SqliteFailure(failure, message) if failure.code == ffi::ErrorCode::ConstraintViolation => {
+                let message = message.as_deref().unwrap_or_default();
+
+                match failure.extended_code {
+                    ffi::SQLITE_CONSTRAINT_UNIQUE | ffi::SQLITE_CONSTRAINT_PRIMARYKEY => {
+                        let column = message.trim_start_matches("UNIQUE constraint failed: ");
+
+                        UNIQUE_CONSTRAINTS.iter()
+                            .find(|(constraint, _)| *constraint == column)
+                            .map(|(_, code)| Error::ConstraintViolation(code))
+                            .unwrap_or(Error::ConstraintViolation(UNIQUE_VIOLATION))
+                    }
+                    ffi::SQLITE_CONSTRAINT_FOREIGNKEY => Error::ConstraintViolation(FOREIGN_KEY_VIOLATION),
+                    _ => Error::ConstraintViolation(CONSTRAINT_VIOLATION),
+                }
+            }
+            _ => Error::Database(error),
+        }
+    }
+}
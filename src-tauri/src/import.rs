@@ -0,0 +1,503 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Local, NaiveDate, TimeZone};
+use tauri::{InvokeError, State};
+
+use crate::asset;
+use crate::broker::{self, Broker};
+use crate::brokerage_note::{self, db};
+use crate::brokerage_note::types::{Brokerage, BrokerageNote, BrokerageOrder};
+use crate::corporate_event;
+use crate::corporate_event::types::{CorporateEvent, CorporateEventType};
+use crate::database::Database;
+use crate::error::{Error, FieldError, NOT_FOUND};
+use crate::import::b3_movements::MovementKind;
+use crate::import::b3_trades::B3Trade;
+use crate::import::types::{BrokerageNotePreview, MovementImportResult, TradeImportPreview, UnresolvedOrder};
+use crate::income;
+use crate::income::types::{Income, IncomeType, INTEREST_ON_EQUITY_WITHHOLDING_RATE};
+use crate::position;
+
+mod b3_movements;
+mod b3_trades;
+mod sinacor;
+pub(crate) mod spreadsheet;
+mod types;
+
+const CANT_READ_PDF_FILE: &str = "CANT_READ_PDF_FILE";
+const NO_BROKERAGE_NOTE_FOUND: &str = "NO_BROKERAGE_NOTE_FOUND";
+const UNKNOWN_BROKER: &str = "UNKNOWN_BROKER";
+
+#[tauri::command(async)]
+pub(crate) fn preview_brokerage_note_pdf(database: State<'_, Database>, file_path: String) -> Result<Vec<BrokerageNotePreview>, InvokeError> {
+    let text = pdf_extract::extract_text(&file_path)
+        .map_err(|error| {
+            println!("Erro ao ler arquivo PDF: {}", error);
+            Error::Parse(CANT_READ_PDF_FILE, error.to_string())
+        })?;
+
+    let notes = sinacor::parse_notes(&text);
+
+    if notes.is_empty() {
+        return Err(Error::NotFound(NO_BROKERAGE_NOTE_FOUND).into());
+    }
+
+    let connection = database.connection()?;
+
+    let mut previews = Vec::new();
+    for note in notes {
+        let broker = broker::select_broker_by_name(&connection, &note.broker_name)?
+            .unwrap_or(Broker {
+                id: 0,
+                name: note.broker_name.clone(),
+            });
+
+        let existing_brokerage_id = match broker.id {
+            0 => None,
+            broker_id => db::select_brokerage_id_by_note_number(&connection, &broker_id, &note.note_number)?,
+        };
+
+        let mut orders = Vec::new();
+        let mut unresolved_orders = Vec::new();
+        for (order_index, order) in note.orders.into_iter().enumerate() {
+            let symbol = match order.symbol {
+                Some(symbol) => Some(symbol),
+                None => resolve_symbol(&connection, &order.specification)?,
+            };
+
+            if symbol.is_none() {
+                unresolved_orders.push(UnresolvedOrder {
+                    order_index,
+                    specification: order.specification,
+                });
+            }
+
+            orders.push(new_order(order.order_type, symbol.unwrap_or_default(), order.amount, order.order_value));
+        }
+
+        let mut brokerage_note = BrokerageNote {
+            brokerage: Brokerage {
+                note_number: Some(note.note_number.clone()),
+                ..new_brokerage(broker, note.settlement_fee, note.emolument_fee, note.broker_fee, note.iss_tax, &note.trading_date)
+            },
+            orders,
+            trade_matches: Vec::new(),
+        };
+        brokerage_note.calc();
+
+        previews.push(BrokerageNotePreview {
+            note_number: note.note_number,
+            broker_name: note.broker_name,
+            existing_brokerage_id,
+            brokerage_note,
+            unresolved_orders,
+        });
+    }
+
+    Ok(previews)
+}
+
+#[tauri::command(async)]
+pub(crate) fn preview_b3_trades(database: State<'_, Database>, file_path: String) -> Result<Vec<TradeImportPreview>, InvokeError> {
+    let spreadsheet = spreadsheet::read_spreadsheet(&file_path)?;
+    let trades = b3_trades::parse_trades(&spreadsheet)?;
+
+    let mut trades_by_note: BTreeMap<(String, NaiveDate), Vec<B3Trade>> = BTreeMap::new();
+    for trade in trades {
+        trades_by_note
+            .entry((trade.broker_name.clone(), trade.trading_date))
+            .or_insert_with(Vec::new)
+            .push(trade);
+    }
+
+    if trades_by_note.is_empty() {
+        return Err(Error::NotFound(NO_BROKERAGE_NOTE_FOUND).into());
+    }
+
+    let connection = database.connection()?;
+
+    let mut previews = Vec::new();
+    for ((broker_name, trading_date), trades) in trades_by_note {
+        let broker = broker::select_broker_by_name(&connection, &broker_name)?
+            .unwrap_or(Broker {
+                id: 0,
+                name: broker_name,
+            });
+
+        let existing_brokerage_id = match broker.id {
+            0 => None,
+            broker_id => db::select_brokerage_id_by_broker_and_date(&connection, &broker_id, &trading_date)?,
+        };
+
+        let mut brokerage_note = BrokerageNote {
+            brokerage: new_brokerage(broker, 0.0, 0.0, 0.0, 0.0, &trading_date),
+            orders: trades
+                .into_iter()
+                .map(|trade| new_order(trade.order_type, trade.symbol, trade.amount, trade.order_value))
+                .collect(),
+            trade_matches: Vec::new(),
+        };
+        brokerage_note.calc();
+
+        previews.push(TradeImportPreview {
+            existing_brokerage_id,
+            brokerage_note,
+        });
+    }
+
+    Ok(previews)
+}
+
+// Notes from PDF files; a note number the broker already has is skipped, since it was imported before
+#[tauri::command(async)]
+pub(crate) fn import_brokerage_notes(database: State<'_, Database>, brokerage_notes: Vec<BrokerageNote>) -> Result<(), InvokeError> {
+    let mut connection = database.connection()?;
+
+    validate_brokerage_notes(&connection, &brokerage_notes)?;
+
+    let transaction = connection.transaction()
+        .map_err(Error::from)?;
+
+    for mut brokerage_note in brokerage_notes {
+        brokerage_note.brokerage.broker = import_broker(&transaction, brokerage_note.brokerage.broker)?;
+
+        let existing_brokerage_id = match &brokerage_note.brokerage.note_number {
+            Some(note_number) => db::select_brokerage_id_by_note_number(&transaction, &brokerage_note.brokerage.broker.id, note_number)?,
+            None => None,
+        };
+
+        if existing_brokerage_id.is_none() {
+            insert_brokerage_note(&transaction, brokerage_note)?;
+        }
+    }
+
+    position::update_realized_results(&transaction)?;
+
+    transaction.commit()
+        .map_err(Error::from)?;
+
+    Ok(())
+}
+
+// Trades from the B3 spreadsheet carry no fees. A day the broker already has a note for is not
+// imported again: the existing note keeps its orders and only takes the fees filled in on the
+// preview.
+#[tauri::command(async)]
+pub(crate) fn import_b3_trades(database: State<'_, Database>, brokerage_notes: Vec<BrokerageNote>) -> Result<(), InvokeError> {
+    let mut connection = database.connection()?;
+
+    validate_brokerage_notes(&connection, &brokerage_notes)?;
+
+    let transaction = connection.transaction()
+        .map_err(Error::from)?;
+
+    for mut brokerage_note in brokerage_notes {
+        brokerage_note.brokerage.broker = import_broker(&transaction, brokerage_note.brokerage.broker)?;
+
+        let trading_date = brokerage_note.brokerage.trading_date.naive_local().date();
+        match db::select_brokerage_id_by_broker_and_date(&transaction, &brokerage_note.brokerage.broker.id, &trading_date)? {
+            Some(brokerage_id) => fill_in_fees(&transaction, &brokerage_id, &brokerage_note.brokerage)?,
+            None => insert_brokerage_note(&transaction, brokerage_note)?,
+        }
+    }
+
+    position::update_realized_results(&transaction)?;
+
+    transaction.commit()
+        .map_err(Error::from)?;
+
+    Ok(())
+}
+
+// Income goes in as one record per broker. Bonus shares and splits are summed over the brokers
+// into a single event per symbol and date, since positions are not kept per broker.
+#[tauri::command(async)]
+pub(crate) fn import_b3_movements(database: State<'_, Database>, file_path: String) -> Result<MovementImportResult, InvokeError> {
+    let spreadsheet = spreadsheet::read_spreadsheet(&file_path)?;
+    let (movements, unclassified) = b3_movements::parse_movements(&spreadsheet)?;
+
+    let mut connection = database.connection()?;
+
+    let transaction = connection.transaction()
+        .map_err(Error::from)?;
+
+    let mut result = MovementImportResult {
+        unclassified,
+        ..MovementImportResult::default()
+    };
+
+    let mut corporate_events: BTreeMap<(String, NaiveDate, bool), CorporateEvent> = BTreeMap::new();
+    for movement in movements {
+        let event_type = match movement.kind {
+            MovementKind::Income(income_type) => {
+                let broker = match broker::select_broker_by_name(&transaction, &movement.broker_name)? {
+                    Some(broker) => broker,
+                    None => broker::insert_broker(&transaction, movement.broker_name)?,
+                };
+
+                // B3 shows the JCP amount actually credited, after the withholding
+                let gross_amount = match income_type {
+                    IncomeType::InterestOnEquity => movement.value / (1.0 - INTEREST_ON_EQUITY_WITHHOLDING_RATE),
+                    _ => movement.value,
+                };
+
+                let mut income = Income {
+                    id: 0,
+                    broker,
+                    symbol: movement.symbol,
+                    income_type,
+                    ex_date: movement.date,
+                    payment_date: movement.date,
+                    gross_amount,
+                    withholding_tax: None,
+                    net_amount: 0.0,
+                };
+                income.calc();
+
+                if income::db::exists_income(&transaction, &income)? {
+                    result.duplicates += 1;
+                } else {
+                    income::db::insert_income(&transaction, &income)?;
+                    result.imported_income += 1;
+                }
+
+                continue;
+            }
+            MovementKind::Bonus => CorporateEventType::Bonus,
+            MovementKind::Split => CorporateEventType::Split,
+        };
+
+        let credited_amount = movement.amount as i64;
+        let unit_cost = movement.unit_price.filter(|_| event_type == CorporateEventType::Bonus);
+
+        let corporate_event = corporate_events
+            .entry((movement.symbol.clone(), movement.date, event_type == CorporateEventType::Bonus))
+            .or_insert_with(|| CorporateEvent {
+                id: 0,
+                symbol: movement.symbol,
+                event_type,
+                ex_date: movement.date,
+                ratio_from: 1.0,
+                ratio_to: 1.0,
+                unit_cost,
+                cash_amount: None,
+                amount: Some(0),
+                targets: Vec::new(),
+            });
+
+        corporate_event.amount = corporate_event.amount.map(|amount| amount + credited_amount);
+    }
+
+    for (_, corporate_event) in corporate_events {
+        if corporate_event::db::exists_corporate_event(&transaction, &corporate_event.symbol, &corporate_event.event_type, &corporate_event.ex_date)? {
+            result.duplicates += 1;
+        } else {
+            corporate_event::db::insert_corporate_event(&transaction, &corporate_event)?;
+            result.imported_corporate_events += 1;
+        }
+    }
+
+    if result.imported_corporate_events > 0 {
+        position::update_realized_results(&transaction)?;
+    }
+
+    transaction.commit()
+        .map_err(Error::from)?;
+
+    Ok(result)
+}
+
+// A company name is only taken once it maps to a single ticker; otherwise the order is left
+// without a symbol, so it cannot be imported until the user picks one.
+fn resolve_symbol(connection: &rusqlite::Connection, specification: &str) -> Result<Option<String>, Error> {
+    match sinacor::split_specification(specification) {
+        Some((trading_name, share_class)) => asset::db::select_symbol_by_trading_name(connection, &trading_name, share_class),
+        None => Ok(None),
+    }
+}
+
+// Every note is checked before anything is written, with fields as "brokerage_notes[0].orders[1].amount"
+fn validate_brokerage_notes(connection: &rusqlite::Connection, brokerage_notes: &[BrokerageNote]) -> Result<(), Error> {
+    let mut errors = Vec::new();
+
+    for (index, brokerage_note) in brokerage_notes.iter().enumerate() {
+        let mut note_errors = brokerage_note.validate();
+
+        let broker = &brokerage_note.brokerage.broker;
+        if broker.id == 0 && broker.name.trim().is_empty() {
+            note_errors.push(FieldError::new("broker", UNKNOWN_BROKER));
+        } else if broker.id != 0 && !broker::exists_broker(connection, &broker.id)? {
+            note_errors.push(FieldError::new("broker", NOT_FOUND));
+        }
+
+        errors.extend(note_errors.into_iter().map(|error| FieldError {
+            field: format!("brokerage_notes[{}].{}", index, error.field),
+            code: error.code,
+        }));
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(Error::Validation(errors))
+    }
+}
+
+fn insert_brokerage_note(transaction: &rusqlite::Transaction, mut brokerage_note: BrokerageNote) -> Result<(), Error> {
+    brokerage_note.calc();
+
+    let brokerage_id = db::insert_new_brokerage(transaction, &brokerage_note.brokerage)?;
+    db::insert_new_brokerage_orders(transaction, &brokerage_id, &brokerage_note.orders)?;
+    asset::register_order_assets(transaction, &brokerage_note.orders)
+}
+
+// Brokers unknown at preview time are only created once the import is confirmed
+fn import_broker(transaction: &rusqlite::Transaction, broker: Broker) -> Result<Broker, Error> {
+    if broker.id != 0 {
+        return Ok(broker);
+    }
+
+    let broker_name = broker.name.trim();
+
+    match broker::select_broker_by_name(transaction, broker_name)? {
+        Some(broker) => Ok(broker),
+        None => broker::insert_broker(transaction, broker_name.to_string()),
+    }
+}
+
+// Fees left at zero on the preview keep the values already recorded
+fn fill_in_fees(transaction: &rusqlite::Transaction, brokerage_id: &i64, brokerage: &Brokerage) -> Result<(), Error> {
+    let mut existing_note = brokerage_note::select_brokerage_note(transaction, brokerage_id)?;
+    let existing_brokerage = &mut existing_note.brokerage;
+
+    let fees = [
+        (&mut existing_brokerage.total_settlement_fee, brokerage.total_settlement_fee),
+        (&mut existing_brokerage.total_emolument_fee, brokerage.total_emolument_fee),
+        (&mut existing_brokerage.total_broker_fee, brokerage.total_broker_fee),
+        (&mut existing_brokerage.total_iss_tax, brokerage.total_iss_tax),
+    ];
+    for (existing_fee, fee) in fees {
+        if fee != 0.0 {
+            *existing_fee = fee;
+        }
+    }
+
+    existing_note.calc();
+
+    db::update_brokerage(transaction, &existing_note.brokerage)?;
+    db::delete_brokerage_orders(transaction, brokerage_id)?;
+    db::insert_new_brokerage_orders(transaction, brokerage_id, &existing_note.orders)
+}
+
+fn new_brokerage(broker: Broker, settlement_fee: f64, emolument_fee: f64, broker_fee: f64, iss_tax: f64, trading_date: &NaiveDate) -> Brokerage {
+    Brokerage {
+        id: 0,
+        broker,
+        note_number: None,
+        total_settlement_fee: settlement_fee,
+        total_emolument_fee: emolument_fee,
+        total_broker_fee: broker_fee,
+        total_iss_tax: iss_tax,
+        total_income_tax: 0.0,
+        total_cost: 0.0,
+        total_transacted: 0.0,
+        total_purchased: 0.0,
+        total_sold: 0.0,
+        trading_date: to_local_date_time(trading_date),
+    }
+}
+
+fn new_order(order_type: String, symbol: String, amount: i64, order_value: f64) -> BrokerageOrder {
+    BrokerageOrder {
+        id: 0,
+        order_type,
+        symbol,
+        amount,
+        order_value,
+        unit_value: 0.0,
+        settlement_fee: 0.0,
+        emolument_fee: 0.0,
+        broker_fee: 0.0,
+        iss_tax: 0.0,
+        income_tax: 0.0,
+        total_cost: 0.0,
+    }
+}
+
+fn to_local_date_time(date: &NaiveDate) -> DateTime<Local> {
+    Local.from_local_date(date)
+        .earliest()
+        .unwrap_or_else(Local::today)
+        .and_hms(0, 0, 0)
+}
+
+pub(crate) fn parse_date(value: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(value.trim(), "%d/%m/%Y").ok()
+}
+
+// Brazilian notation: "2.530,00" or "R$ 1.000"
+pub(crate) fn parse_decimal(value: &str) -> Option<f64> {
+    let value = value.trim().trim_start_matches("R$").trim();
+
+    if value.is_empty() || !value.chars().any(|char| char.is_ascii_digit()) {
+        return None;
+    }
+
+    value.replace('.', "")
+        .replace(',', ".")
+        .parse::<f64>()
+        .ok()
+}
+
+// Spreadsheet cells: B3 CSV files use a dot as decimal separator ("25.30"), while spreadsheets
+// saved in Brazil use a comma
+pub(crate) fn parse_number(value: &str) -> Option<f64> {
+    let value = value.trim().trim_start_matches("R$").trim();
+
+    if value.contains(',') { parse_decimal(value) } else { value.parse().ok() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database;
+
+    #[test]
+    fn numbers_in_either_notation() {
+        assert_eq!(parse_number("2.530,00"), Some(2530.0));
+        assert_eq!(parse_number("R$ 25,30"), Some(25.3));
+        assert_eq!(parse_number("25.30"), Some(25.3));
+        assert_eq!(parse_number("100"), Some(100.0));
+        assert_eq!(parse_number("-"), None);
+        assert_eq!(parse_decimal("1.000"), Some(1000.0));
+    }
+
+    #[test]
+    fn import_validation_reports_note_indices() {
+        let connection = database::open_in_memory();
+
+        let valid_note = BrokerageNote {
+            brokerage: new_brokerage(Broker { id: 0, name: "XP".to_string() }, 0.0, 0.0, 0.0, 0.0, &NaiveDate::from_ymd(2021, 3, 5)),
+            orders: vec![new_order("C".to_string(), "PETR4".to_string(), 100, 2530.0)],
+            trade_matches: Vec::new(),
+        };
+        let invalid_note = BrokerageNote {
+            brokerage: new_brokerage(Broker { id: 7, name: "CLEAR".to_string() }, 0.0, 0.0, 0.0, 0.0, &NaiveDate::from_ymd(2021, 3, 5)),
+            orders: vec![new_order("X".to_string(), String::new(), 0, 10.0)],
+            trade_matches: Vec::new(),
+        };
+
+        let errors = match validate_brokerage_notes(&connection, &[valid_note, invalid_note]) {
+            Err(Error::Validation(errors)) => errors,
+            result => panic!("expected validation errors, got {:?}", result),
+        };
+
+        let fields: Vec<(&str, &str)> = errors.iter().map(|error| (error.field.as_str(), error.code.as_str())).collect();
+        assert_eq!(fields, vec![
+            ("brokerage_notes[1].orders[0].order_type", "UNKNOWN_VALUE"),
+            ("brokerage_notes[1].orders[0].symbol", "REQUIRED"),
+            ("brokerage_notes[1].orders[0].amount", "MUST_BE_POSITIVE"),
+            ("brokerage_notes[1].broker", "NOT_FOUND"),
+        ]);
+    }
+}
@@ -0,0 +1,96 @@
+use chrono::NaiveDate;
+
+use crate::error::Error;
+use crate::import::spreadsheet::{cell, Spreadsheet};
+use crate::import::types::UnclassifiedMovement;
+use crate::import::{parse_date, parse_number};
+use crate::income::types::IncomeType;
+
+const INVALID_B3_MOVEMENTS_FILE: &str = "INVALID_B3_MOVEMENTS_FILE";
+
+pub(crate) enum MovementKind {
+    Income(IncomeType),
+    Bonus,
+    Split,
+}
+
+pub(crate) struct B3Movement {
+    pub(crate) kind: MovementKind,
+    pub(crate) date: NaiveDate,
+    pub(crate) broker_name: String,
+    pub(crate) symbol: String,
+    pub(crate) amount: f64,
+    pub(crate) unit_price: Option<f64>,
+    pub(crate) value: f64,
+}
+
+// Trade settlements are left out since trades come from the brokerage notes
+pub(crate) fn parse_movements(spreadsheet: &Spreadsheet) -> Result<(Vec<B3Movement>, Vec<UnclassifiedMovement>), Error> {
+    let direction_column = spreadsheet.column(&["Entrada/Saída", "Entrada/Saida"]);
+    let date_column = spreadsheet.column(&["Data"]);
+    let type_column = spreadsheet.column(&["Movimentação", "Movimentacao"]);
+    let product_column = spreadsheet.column(&["Produto"]);
+    let broker_column = spreadsheet.column(&["Instituição", "Instituicao"]);
+    let amount_column = spreadsheet.column(&["Quantidade"]);
+    let price_column = spreadsheet.column(&["Preço unitário", "Preco unitario"]);
+    let value_column = spreadsheet.column(&["Valor da Operação", "Valor da Operacao"]);
+
+    let (direction_column, date_column, type_column, product_column, broker_column, amount_column, price_column, value_column) =
+        match (direction_column, date_column, type_column, product_column, broker_column, amount_column, price_column, value_column) {
+            (Some(direction), Some(date), Some(movement_type), Some(product), Some(broker), Some(amount), Some(price), Some(value)) =>
+                (direction, date, movement_type, product, broker, amount, price, value),
+            _ => return Err(Error::Parse(INVALID_B3_MOVEMENTS_FILE, "colunas obrigatórias não encontradas".to_string())),
+        };
+
+    let mut movements = Vec::new();
+    let mut unclassified = Vec::new();
+    for row in &spreadsheet.rows {
+        let movement_type = cell(row, type_column).to_uppercase();
+        if movement_type.starts_with("TRANSFERÊNCIA - LIQUIDAÇÃO") || movement_type.starts_with("TRANSFERENCIA - LIQUIDACAO") {
+            continue;
+        }
+
+        let direction = cell(row, direction_column).to_uppercase();
+        let is_credit = direction.starts_with("CREDITO") || direction.starts_with("CRÉDITO");
+
+        let kind = match movement_type.as_str() {
+            _ if !is_credit => None,
+            "DIVIDENDO" => Some(MovementKind::Income(IncomeType::Dividend)),
+            "JUROS SOBRE CAPITAL PRÓPRIO" | "JUROS SOBRE CAPITAL PROPRIO" => Some(MovementKind::Income(IncomeType::InterestOnEquity)),
+            "RENDIMENTO" => Some(MovementKind::Income(IncomeType::RealEstateFundIncome)),
+            "BONIFICAÇÃO EM ATIVOS" | "BONIFICACAO EM ATIVOS" => Some(MovementKind::Bonus),
+            "DESDOBRO" => Some(MovementKind::Split),
+            _ => None,
+        };
+
+        let date = parse_date(cell(row, date_column));
+        let symbol = cell(row, product_column)
+            .split(" - ")
+            .next()
+            .unwrap_or("")
+            .trim()
+            .to_uppercase();
+
+        match (kind, date) {
+            (Some(kind), Some(date)) if !symbol.is_empty() => movements.push(B3Movement {
+                kind,
+                date,
+                broker_name: cell(row, broker_column).to_string(),
+                symbol,
+                amount: parse_number(cell(row, amount_column)).unwrap_or(0.0),
+                unit_price: parse_number(cell(row, price_column)),
+                value: parse_number(cell(row, value_column)).unwrap_or(0.0),
+            }),
+            _ => unclassified.push(UnclassifiedMovement {
+                date: cell(row, date_column).to_string(),
+                movement_type: cell(row, type_column).to_string(),
+                product: cell(row, product_column).to_string(),
+                broker_name: cell(row, broker_column).to_string(),
+                amount: cell(row, amount_column).to_string(),
+                value: cell(row, value_column).to_string(),
+            }),
+        }
+    }
+
+    Ok((movements, unclassified))
+}
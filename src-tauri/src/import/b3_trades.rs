@@ -0,0 +1,72 @@
+use chrono::NaiveDate;
+
+use crate::error::Error;
+use crate::import::spreadsheet::{cell, Spreadsheet};
+use crate::import::{parse_date, parse_number};
+
+const INVALID_B3_TRADES_FILE: &str = "INVALID_B3_TRADES_FILE";
+
+pub(crate) struct B3Trade {
+    pub(crate) trading_date: NaiveDate,
+    pub(crate) broker_name: String,
+    pub(crate) order_type: String,
+    pub(crate) symbol: String,
+    pub(crate) amount: i64,
+    pub(crate) order_value: f64,
+}
+
+pub(crate) fn parse_trades(spreadsheet: &Spreadsheet) -> Result<Vec<B3Trade>, Error> {
+    let date_column = spreadsheet.column(&["Data do Negócio", "Data do Negocio"]);
+    let type_column = spreadsheet.column(&["Tipo de Movimentação", "Tipo de Movimentacao"]);
+    let broker_column = spreadsheet.column(&["Instituição", "Instituicao"]);
+    let symbol_column = spreadsheet.column(&["Código de Negociação", "Codigo de Negociacao"]);
+    let amount_column = spreadsheet.column(&["Quantidade"]);
+    let value_column = spreadsheet.column(&["Valor"]);
+
+    let (date_column, type_column, broker_column, symbol_column, amount_column, value_column) =
+        match (date_column, type_column, broker_column, symbol_column, amount_column, value_column) {
+            (Some(date), Some(order_type), Some(broker), Some(symbol), Some(amount), Some(value)) =>
+                (date, order_type, broker, symbol, amount, value),
+            _ => return Err(Error::Parse(INVALID_B3_TRADES_FILE, "colunas obrigatórias não encontradas".to_string())),
+        };
+
+    let mut trades = Vec::new();
+    for row in &spreadsheet.rows {
+        let order_type = match cell(row, type_column).to_uppercase().as_str() {
+            "COMPRA" => "C",
+            "VENDA" => "V",
+            _ => continue,
+        };
+
+        let trading_date = match parse_date(cell(row, date_column)) {
+            Some(trading_date) => trading_date,
+            None => continue,
+        };
+
+        let (amount, order_value) = match (parse_number(cell(row, amount_column)), parse_number(cell(row, value_column))) {
+            (Some(amount), Some(order_value)) => (amount as i64, order_value),
+            _ => continue,
+        };
+
+        trades.push(B3Trade {
+            trading_date,
+            broker_name: cell(row, broker_column).to_string(),
+            order_type: order_type.to_string(),
+            symbol: normalize_symbol(cell(row, symbol_column)),
+            amount,
+            order_value,
+        });
+    }
+
+    Ok(trades)
+}
+
+// Fractional market trades use the main ticker with an "F" suffix
+fn normalize_symbol(symbol: &str) -> String {
+    let symbol = symbol.to_uppercase();
+
+    match symbol.strip_suffix('F') {
+        Some(main_symbol) if main_symbol.ends_with(|char: char| char.is_ascii_digit()) => main_symbol.to_string(),
+        _ => symbol,
+    }
+}
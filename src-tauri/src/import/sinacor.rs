@@ -0,0 +1,339 @@
+use chrono::NaiveDate;
+
+use crate::import::{parse_date, parse_decimal};
+
+const NOTE_HEADER: &str = "NOTA DE CORRETAGEM";
+const NOTE_NUMBER_LABEL: &str = "NR. NOTA";
+const ORDER_LINE_PREFIXES: [&str; 3] = ["1-BOVESPA", "B3 RV LISTADO", "BOVESPA"];
+const SETTLEMENT_FEE_LABELS: [&str; 1] = ["TAXA DE LIQUIDA"];
+const EMOLUMENT_FEE_LABELS: [&str; 1] = ["EMOLUMENTOS"];
+const BROKER_FEE_LABELS: [&str; 2] = ["TAXA OPERACIONAL", "CORRETAGEM"];
+const ISS_TAX_LABELS: [&str; 1] = ["ISS"];
+const SHARE_CLASSES: [&str; 13] = ["ON", "PN", "PNA", "PNB", "PNC", "PND", "UNT", "CI", "DRN", "DR1", "DR2", "DR3", "ETF"];
+const MARKET_TYPE_WORDS: [&str; 11] = ["VISTA", "FRACIONARIO", "FRACIONÁRIO", "OPCAO", "OPÇÃO", "DE", "COMPRA", "VENDA", "EXERC", "OPC", "TERMO"];
+
+pub(crate) struct SinacorOrder {
+    pub(crate) order_type: String,
+    pub(crate) symbol: Option<String>,
+    pub(crate) specification: String,
+    pub(crate) amount: i64,
+    pub(crate) order_value: f64,
+}
+
+pub(crate) struct SinacorNote {
+    pub(crate) note_number: String,
+    pub(crate) broker_name: String,
+    pub(crate) trading_date: NaiveDate,
+    pub(crate) orders: Vec<SinacorOrder>,
+    pub(crate) settlement_fee: f64,
+    pub(crate) emolument_fee: f64,
+    pub(crate) broker_fee: f64,
+    pub(crate) iss_tax: f64,
+}
+
+impl SinacorNote {
+    // Multi-page notes repeat the header on every "folha" but only the last one has the summary
+    fn merge(&mut self, page: SinacorNote) {
+        self.orders.extend(page.orders);
+
+        if page.settlement_fee != 0.0 { self.settlement_fee = page.settlement_fee; }
+        if page.emolument_fee != 0.0 { self.emolument_fee = page.emolument_fee; }
+        if page.broker_fee != 0.0 { self.broker_fee = page.broker_fee; }
+        if page.iss_tax != 0.0 { self.iss_tax = page.iss_tax; }
+    }
+}
+
+pub(crate) fn parse_notes(text: &str) -> Vec<SinacorNote> {
+    let mut notes: Vec<SinacorNote> = Vec::new();
+
+    for page in text.split(NOTE_HEADER).skip(1) {
+        let lines: Vec<&str> = page.lines()
+            .map(|line| line.trim())
+            .filter(|line| !line.is_empty())
+            .collect();
+
+        let page_note = match parse_page(&lines) {
+            Some(page_note) => page_note,
+            None => continue,
+        };
+
+        match notes.iter_mut().find(|note| note.note_number == page_note.note_number) {
+            Some(note) => note.merge(page_note),
+            None => notes.push(page_note),
+        }
+    }
+
+    notes.retain(|note| !note.orders.is_empty());
+    notes
+}
+
+fn parse_page(lines: &[&str]) -> Option<SinacorNote> {
+    let header_index = lines.iter()
+        .position(|line| line.to_uppercase().contains(NOTE_NUMBER_LABEL))?;
+
+    let (values_index, note_number, trading_date) = lines.iter()
+        .enumerate()
+        .skip(header_index)
+        .find_map(|(index, line)| parse_header_values(line).map(|(number, date)| (index, number, date)))?;
+
+    let broker_name = lines.get(values_index + 1)
+        .map(|line| line.to_string())
+        .unwrap_or_default();
+
+    let orders = lines.iter()
+        .filter(|line| {
+            let line = line.to_uppercase();
+            ORDER_LINE_PREFIXES.iter().any(|prefix| line.starts_with(prefix))
+        })
+        .filter_map(|line| parse_order(line))
+        .collect();
+
+    Some(SinacorNote {
+        note_number,
+        broker_name,
+        trading_date,
+        orders,
+        settlement_fee: find_fee(lines, &SETTLEMENT_FEE_LABELS),
+        emolument_fee: find_fee(lines, &EMOLUMENT_FEE_LABELS),
+        broker_fee: find_fee(lines, &BROKER_FEE_LABELS),
+        iss_tax: find_fee(lines, &ISS_TAX_LABELS),
+    })
+}
+
+fn parse_header_values(line: &str) -> Option<(String, NaiveDate)> {
+    let tokens: Vec<&str> = line.split_whitespace().collect();
+
+    let date_index = tokens.iter().position(|token| parse_date(token).is_some())?;
+    let note_number = tokens[..date_index].iter()
+        .find(|token| token.chars().all(|char| char.is_ascii_digit() || char == '.'))?;
+
+    Some((note_number.replace('.', ""), parse_date(tokens[date_index])?))
+}
+
+// e.g. "1-BOVESPA C VISTA PETR4 PN N2 # 100 25,30 2.530,00 D", where the security may also be
+// the company name alone, as in "PETROBRAS PN N2"
+fn parse_order(line: &str) -> Option<SinacorOrder> {
+    let mut tokens: Vec<&str> = line.split_whitespace().collect();
+
+    if matches!(tokens.last(), Some(&"D") | Some(&"C")) {
+        tokens.pop();
+    }
+
+    if tokens.len() < 5 {
+        return None;
+    }
+
+    let order_value = parse_decimal(tokens[tokens.len() - 1])?;
+    let amount = parse_decimal(tokens[tokens.len() - 3])? as i64;
+
+    let type_index = tokens.iter().position(|token| *token == "C" || *token == "V")?;
+    let mut specification = &tokens[type_index + 1..tokens.len() - 3];
+
+    // Market type and term come before the security, observations such as "#" or "D" after it
+    while let Some((first, rest)) = specification.split_first() {
+        if !MARKET_TYPE_WORDS.contains(&first.to_uppercase().as_str()) && !is_term(first) {
+            break;
+        }
+        specification = rest;
+    }
+
+    while let Some((last, rest)) = specification.split_last() {
+        if last.chars().count() > 1 && !last.contains('#') {
+            break;
+        }
+        specification = rest;
+    }
+
+    let symbol = specification.iter()
+        .find(|token| is_symbol(token))
+        .map(|token| token.trim_end_matches('F').to_string());
+
+    Some(SinacorOrder {
+        order_type: tokens[type_index].to_string(),
+        symbol,
+        specification: specification.join(" "),
+        amount,
+        order_value,
+    })
+}
+
+// "PETROBRAS PN N2" into the trading name "PETROBRAS" and the share class "PN"
+pub(crate) fn split_specification(specification: &str) -> Option<(String, &str)> {
+    let tokens: Vec<&str> = specification.split_whitespace().collect();
+    let class_index = tokens.iter().position(|token| SHARE_CLASSES.contains(&token.to_uppercase().as_str()))?;
+
+    if class_index == 0 {
+        return None;
+    }
+
+    Some((tokens[..class_index].join(" ").to_uppercase(), tokens[class_index]))
+}
+
+// Expiration of options and forwards, e.g. "03/21"
+fn is_term(token: &str) -> bool {
+    token.len() == 5 && token.get(2..3) == Some("/") && token.chars().filter(|char| char.is_ascii_digit()).count() == 4
+}
+
+// PETR4, TAEE11, PETRC250 or PETR4F on the fractional market
+fn is_symbol(token: &str) -> bool {
+    let token = token.trim_end_matches('F');
+
+    token.len() >= 5 &&
+        token.len() <= 8 &&
+        token.chars().take(4).all(|char| char.is_ascii_uppercase()) &&
+        token.chars().skip(4).all(|char| char.is_ascii_alphanumeric()) &&
+        token.chars().last().map_or(false, |char| char.is_ascii_digit())
+}
+
+fn find_fee(lines: &[&str], labels: &[&str]) -> f64 {
+    let label_index = lines.iter()
+        .position(|line| {
+            let line = line.to_uppercase();
+            labels.iter().any(|label| line.starts_with(label))
+        });
+
+    label_index
+        .and_then(|index| {
+            lines[index..].iter()
+                .take(2)
+                .find_map(|line| {
+                    line.split_whitespace()
+                        .rev()
+                        .filter(|token| token.contains(','))
+                        .find_map(|token| parse_decimal(token))
+                })
+        })
+        .unwrap_or(0.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // As extracted from a SINACOR PDF, with the security given as the company name
+    const NAMED_NOTE: &str = "
+NOTA DE CORRETAGEM
+Nr. nota Folha Data pregão
+87654 1 05/03/2021
+XP INVESTIMENTOS CCTVM S/A
+Av. Ataulfo de Paiva, 153 Sala 201 - Leblon - Rio de Janeiro - RJ
+Negócios realizados
+Q Negociação C/V Tipo mercado Prazo Especificação do título Obs. (*) Quantidade Preço / Ajuste Valor Operação / Ajuste D/C
+1-BOVESPA C VISTA PETROBRAS PN N2 100 25,30 2.530,00 D
+1-BOVESPA V VISTA ITAUSA PN N1 # 200 10,00 2.000,00 C
+1-BOVESPA C VISTA VALE ON NM D 10 80,00 800,00 D
+Resumo dos Negócios Resumo Financeiro
+Valor das operações 5.330,00
+Clearing
+Valor líquido das operações 3.330,00 D
+Taxa de liquidação 1,33 D
+Taxa de Registro 0,00 D
+Bolsa
+Taxa de termo/opções 0,00 D
+Taxa A.N.A. 0,00 D
+Emolumentos 0,27 D
+Custos Operacionais
+Taxa Operacional 4,90 D
+ISS (SÃO PAULO) 0,24 D
+";
+
+    // Two "folhas" of the same note, with tickers, and the summary only on the last one
+    const TICKER_NOTE: &str = "
+NOTA DE CORRETAGEM
+Nr. nota Folha Data pregão
+1.234 1 10/05/2021
+CLEAR CORRETORA - GRUPO XP
+Negócios realizados
+B3 RV LISTADO C VISTA PETR4 PN N2 100 25,30 2.530,00 D
+B3 RV LISTADO C FRACIONARIO BBAS3F ON NM 5 30,00 150,00 D
+CONTINUA...
+NOTA DE CORRETAGEM
+Nr. nota Folha Data pregão
+1.234 2 10/05/2021
+CLEAR CORRETORA - GRUPO XP
+Negócios realizados
+B3 RV LISTADO V OPCAO DE VENDA 06/21 PETRR250 PN 25,00 PETR 100 1,20 120,00 C
+Resumo Financeiro
+Taxa de liquidação 0,70 D
+Emolumentos 0,08 D
+Corretagem 0,00 D
+ISS 0,00 D
+";
+
+    #[test]
+    fn parses_note_with_company_names() {
+        let notes = parse_notes(NAMED_NOTE);
+
+        assert_eq!(notes.len(), 1);
+        let note = &notes[0];
+
+        assert_eq!(note.note_number, "87654");
+        assert_eq!(note.broker_name, "XP INVESTIMENTOS CCTVM S/A");
+        assert_eq!(note.trading_date, NaiveDate::from_ymd(2021, 3, 5));
+
+        let orders: Vec<(&str, Option<&str>, &str, i64, f64)> = note.orders.iter()
+            .map(|order| (order.order_type.as_str(), order.symbol.as_deref(), order.specification.as_str(), order.amount, order.order_value))
+            .collect();
+        assert_eq!(orders, vec![
+            ("C", None, "PETROBRAS PN N2", 100, 2530.0),
+            ("V", None, "ITAUSA PN N1", 200, 2000.0),
+            ("C", None, "VALE ON NM", 10, 800.0),
+        ]);
+
+        assert_eq!(note.settlement_fee, 1.33);
+        assert_eq!(note.emolument_fee, 0.27);
+        assert_eq!(note.broker_fee, 4.90);
+        assert_eq!(note.iss_tax, 0.24);
+    }
+
+    #[test]
+    fn merges_pages_of_note_with_tickers() {
+        let notes = parse_notes(TICKER_NOTE);
+
+        assert_eq!(notes.len(), 1);
+        let note = &notes[0];
+
+        assert_eq!(note.note_number, "1234");
+        assert_eq!(note.broker_name, "CLEAR CORRETORA - GRUPO XP");
+        assert_eq!(note.trading_date, NaiveDate::from_ymd(2021, 5, 10));
+
+        let orders: Vec<(&str, Option<&str>, i64, f64)> = note.orders.iter()
+            .map(|order| (order.order_type.as_str(), order.symbol.as_deref(), order.amount, order.order_value))
+            .collect();
+        assert_eq!(orders, vec![
+            ("C", Some("PETR4"), 100, 2530.0),
+            ("C", Some("BBAS3"), 5, 150.0),
+            ("V", Some("PETRR250"), 100, 120.0),
+        ]);
+
+        assert_eq!(note.settlement_fee, 0.70);
+        assert_eq!(note.emolument_fee, 0.08);
+        assert_eq!(note.broker_fee, 0.0);
+    }
+
+    #[test]
+    fn ignores_text_without_orders() {
+        assert!(parse_notes("").is_empty());
+        assert!(parse_notes("NOTA DE CORRETAGEM\nNr. nota Folha Data pregão\n1 1 05/03/2021\nCORRETORA\n").is_empty());
+    }
+
+    #[test]
+    fn splits_specification_into_trading_name_and_class() {
+        assert_eq!(split_specification("PETROBRAS PN N2"), Some(("PETROBRAS".to_string(), "PN")));
+        assert_eq!(split_specification("ITAU UNIBANCO PN N1"), Some(("ITAU UNIBANCO".to_string(), "PN")));
+        assert_eq!(split_specification("SANEPAR UNT N2"), Some(("SANEPAR".to_string(), "UNT")));
+        assert_eq!(split_specification("PN N2"), None);
+        assert_eq!(split_specification("PETROBRAS"), None);
+    }
+
+    #[test]
+    fn recognizes_symbols() {
+        assert!(is_symbol("PETR4"));
+        assert!(is_symbol("TAEE11"));
+        assert!(is_symbol("BBAS3F"));
+        assert!(is_symbol("PETRC250"));
+        assert!(!is_symbol("PETROBRAS"));
+        assert!(!is_symbol("PN"));
+        assert!(!is_symbol("N2"));
+    }
+}
@@ -0,0 +1,173 @@
+use std::fs;
+use std::path::Path;
+
+use calamine::{open_workbook_auto, Data, Reader};
+use chrono::{Duration, NaiveDate};
+
+use crate::error::Error;
+
+const CANT_READ_SPREADSHEET_FILE: &str = "CANT_READ_SPREADSHEET_FILE";
+const INVALID_SPREADSHEET_FILE: &str = "INVALID_SPREADSHEET_FILE";
+
+pub(crate) struct Spreadsheet {
+    columns: Vec<String>,
+    pub(crate) rows: Vec<Vec<String>>,
+}
+
+impl Spreadsheet {
+    pub(crate) fn column(&self, names: &[&str]) -> Option<usize> {
+        self.columns.iter()
+            .position(|column| names.iter().any(|name| column == &name.to_uppercase()))
+    }
+}
+
+pub(crate) fn cell(row: &[String], index: usize) -> &str {
+    row.get(index).map(|value| value.trim()).unwrap_or("")
+}
+
+pub(crate) fn read_spreadsheet(file_path: &str) -> Result<Spreadsheet, Error> {
+    let is_csv = Path::new(file_path)
+        .extension()
+        .map_or(false, |extension| extension.eq_ignore_ascii_case("csv"));
+
+    let mut rows = if is_csv { read_csv_rows(file_path)? } else { read_workbook_rows(file_path)? }
+        .into_iter()
+        .filter(|row| row.iter().any(|value| !value.trim().is_empty()));
+
+    let columns = rows.next()
+        .unwrap_or_default()
+        .iter()
+        .map(|column| column.trim().to_uppercase())
+        .collect();
+
+    Ok(Spreadsheet {
+        columns,
+        rows: rows.collect(),
+    })
+}
+
+fn read_csv_rows(file_path: &str) -> Result<Vec<Vec<String>>, Error> {
+    let bytes = fs::read(file_path)
+        .map_err(|error| {
+            println!("Erro ao ler planilha: {}", error);
+            Error::Io(CANT_READ_SPREADSHEET_FILE, error)
+        })?;
+
+    // The B3 portal and Excel in Portuguese save CSV files in ISO-8859-1
+    let content = String::from_utf8(bytes)
+        .unwrap_or_else(|error| error.into_bytes().iter().map(|byte| *byte as char).collect());
+    let content = content.trim_start_matches('\u{feff}');
+
+    let delimiter = if content.lines().next().map_or(false, |line| line.contains(';')) { b';' } else { b',' };
+
+    csv::ReaderBuilder::new()
+        .delimiter(delimiter)
+        .has_headers(false)
+        .flexible(true)
+        .from_reader(content.as_bytes())
+        .records()
+        .map(|record| {
+            record
+                .map(|record| record.iter().map(|value| value.to_string()).collect())
+                .map_err(|error| {
+                    println!("Erro ao interpretar planilha: {}", error);
+                    Error::Parse(INVALID_SPREADSHEET_FILE, error.to_string())
+                })
+        })
+        .collect()
+}
+
+fn read_workbook_rows(file_path: &str) -> Result<Vec<Vec<String>>, Error> {
+    let mut workbook = open_workbook_auto(file_path).map_err(workbook_error)?;
+
+    let range = workbook.worksheet_range_at(0)
+        .ok_or_else(|| Error::Parse(INVALID_SPREADSHEET_FILE, "planilha vazia".to_string()))?
+        .map_err(workbook_error)?;
+
+    Ok(
+        range.rows()
+            .map(|row| row.iter().map(cell_to_string).collect())
+            .collect()
+    )
+}
+
+fn workbook_error(error: calamine::Error) -> Error {
+    match error {
+        calamine::Error::Io(error) => {
+            println!("Erro ao ler planilha: {}", error);
+            Error::Io(CANT_READ_SPREADSHEET_FILE, error)
+        }
+        error => {
+            println!("Erro ao interpretar planilha: {}", error);
+            Error::Parse(INVALID_SPREADSHEET_FILE, error.to_string())
+        }
+    }
+}
+
+// Numbers are written back in Brazilian notation, which parse_number reads like CSV cells
+fn cell_to_string(cell: &Data) -> String {
+    match cell {
+        Data::String(value) => value.to_string(),
+        Data::Int(value) => value.to_string(),
+        Data::Float(value) => value.to_string().replace('.', ","),
+        Data::DateTime(value) => {
+            let date = NaiveDate::from_ymd(1899, 12, 30) + Duration::days(value.as_f64() as i64);
+            date.format("%d/%m/%Y").to_string()
+        }
+        Data::DateTimeIso(value) => value.to_string(),
+        _ => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use super::*;
+
+    #[test]
+    fn reads_latin1_csv() {
+        let file_path = env::temp_dir().join("consolidacao_latin1.csv");
+        // "Código;Preço" with ç and ó in ISO-8859-1
+        fs::write(&file_path, b"C\xf3digo;Pre\xe7o\nPETR4;25.30\n").unwrap();
+
+        let spreadsheet = read_spreadsheet(file_path.to_str().unwrap()).unwrap();
+        fs::remove_file(&file_path).unwrap();
+
+        assert_eq!(spreadsheet.column(&["Código"]), Some(0));
+        assert_eq!(spreadsheet.column(&["Preço"]), Some(1));
+        assert_eq!(spreadsheet.rows, vec![vec!["PETR4".to_string(), "25.30".to_string()]]);
+    }
+
+    #[test]
+    fn reads_utf8_csv_with_bom() {
+        let file_path = env::temp_dir().join("consolidacao_utf8.csv");
+        fs::write(&file_path, "\u{feff}Código,Preço\nVALE3,\"80,00\"\n").unwrap();
+
+        let spreadsheet = read_spreadsheet(file_path.to_str().unwrap()).unwrap();
+        fs::remove_file(&file_path).unwrap();
+
+        assert_eq!(spreadsheet.column(&["Código"]), Some(0));
+        assert_eq!(spreadsheet.rows, vec![vec!["VALE3".to_string(), "80,00".to_string()]]);
+    }
+
+    #[test]
+    fn missing_file_is_an_io_error() {
+        let file_path = env::temp_dir().join("planilha_inexistente.csv");
+
+        let error = read_spreadsheet(file_path.to_str().unwrap()).err().unwrap();
+
+        assert!(matches!(error, Error::Io(CANT_READ_SPREADSHEET_FILE, _)));
+    }
+
+    #[test]
+    fn unreadable_workbook_is_a_parse_error() {
+        let file_path = env::temp_dir().join("planilha_invalida.xlsx");
+        fs::write(&file_path, "não é uma planilha").unwrap();
+
+        let error = read_spreadsheet(file_path.to_str().unwrap()).err().unwrap();
+        fs::remove_file(&file_path).unwrap();
+
+        assert!(matches!(error, Error::Parse(INVALID_SPREADSHEET_FILE, _)));
+    }
+}
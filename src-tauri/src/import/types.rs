@@ -0,0 +1,43 @@
+use serde::{Deserialize, Serialize};
+
+use crate::brokerage_note::types::BrokerageNote;
+
+#[derive(Serialize, Deserialize, Debug)]
+pub(crate) struct BrokerageNotePreview {
+    pub(crate) note_number: String,
+    pub(crate) broker_name: String,
+    pub(crate) existing_brokerage_id: Option<i64>,
+    pub(crate) brokerage_note: BrokerageNote,
+    pub(crate) unresolved_orders: Vec<UnresolvedOrder>,
+}
+
+// Orders whose security could not be resolved to a ticker are previewed with an empty symbol
+#[derive(Serialize, Deserialize, Debug)]
+pub(crate) struct UnresolvedOrder {
+    pub(crate) order_index: usize,
+    pub(crate) specification: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub(crate) struct TradeImportPreview {
+    pub(crate) existing_brokerage_id: Option<i64>,
+    pub(crate) brokerage_note: BrokerageNote,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub(crate) struct UnclassifiedMovement {
+    pub(crate) date: String,
+    pub(crate) movement_type: String,
+    pub(crate) product: String,
+    pub(crate) broker_name: String,
+    pub(crate) amount: String,
+    pub(crate) value: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub(crate) struct MovementImportResult {
+    pub(crate) imported_income: usize,
+    pub(crate) imported_corporate_events: usize,
+    pub(crate) duplicates: usize,
+    pub(crate) unclassified: Vec<UnclassifiedMovement>,
+}
@@ -0,0 +1,75 @@
+use tauri::{InvokeError, State};
+
+use crate::commons::page::{PageRequest, PageResponse};
+use crate::database::Database;
+use crate::error::{Error, FieldError, MUST_BE_POSITIVE};
+use crate::income::types::Income;
+
+pub(crate) mod db;
+pub(crate) mod types;
+
+#[tauri::command(async)]
+pub(crate) fn get_income_page(database: State<'_, Database>, page_request: PageRequest, broker_id: Option<i64>) -> Result<PageResponse<Income>, InvokeError> {
+    if page_request.size <= 0 {
+        return Err(Error::Validation(vec![FieldError::new("size", MUST_BE_POSITIVE)]).into());
+    }
+
+    let connection = database.connection()?;
+
+    let income_list = db::select_income_page(&connection, &page_request, &broker_id)?;
+    let total_elements = db::count_total_income(&connection, &broker_id)?;
+
+    let total_pages = (total_elements / page_request.size) + (if total_elements % page_request.size != 0 { 1 } else { 0 });
+
+    Ok(PageResponse::new(income_list, total_pages, total_elements))
+}
+
+#[tauri::command(async)]
+pub(crate) fn get_income(database: State<'_, Database>, income_id: i64) -> Result<Income, InvokeError> {
+    let connection = database.connection()?;
+
+    Ok(db::select_income_by_id(&connection, &income_id)?)
+}
+
+#[tauri::command(async)]
+pub(crate) fn new_income(database: State<'_, Database>, mut income: Income) -> Result<Income, InvokeError> {
+    validate_income(&income)?;
+
+    let connection = database.connection()?;
+
+    income.symbol = income.symbol.trim().to_uppercase();
+    income.calc();
+    income.id = db::insert_income(&connection, &income)?;
+
+    Ok(income)
+}
+
+#[tauri::command(async)]
+pub(crate) fn update_income(database: State<'_, Database>, mut income: Income) -> Result<Income, InvokeError> {
+    validate_income(&income)?;
+
+    let connection = database.connection()?;
+
+    income.symbol = income.symbol.trim().to_uppercase();
+    income.calc();
+    db::update_income(&connection, &income)?;
+
+    Ok(income)
+}
+
+#[tauri::command(async)]
+pub(crate) fn delete_income(database: State<'_, Database>, income_id: i64) -> Result<(), InvokeError> {
+    let connection = database.connection()?;
+
+    Ok(db::delete_income(&connection, &income_id)?)
+}
+
+fn validate_income(income: &Income) -> Result<(), Error> {
+    let errors = income.validate();
+
+    if !errors.is_empty() {
+        return Err(Error::Validation(errors));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,213 @@
+use rusqlite::named_params;
+
+use crate::broker::Broker;
+use crate::commons::page::PageRequest;
+use crate::commons::{QueryMapper, ToVec};
+use crate::error::Error;
+use crate::income::types::Income;
+
+const INCOME_MAPPER: QueryMapper<Income> = |row| {
+    Ok(Income {
+        id: row.get(0)?,
+        broker: Broker {
+            id: row.get(1)?,
+            name: row.get(2)?,
+        },
+        symbol: row.get(3)?,
+        income_type: row.get(4)?,
+        ex_date: row.get(5)?,
+        payment_date: row.get(6)?,
+        gross_amount: row.get(7)?,
+        withholding_tax: row.get(8)?,
+        net_amount: row.get(9)?,
+    })
+};
+
+pub(crate) fn select_income_page(connection: &rusqlite::Connection, page_request: &PageRequest, broker_id: &Option<i64>) -> Result<Vec<Income>, Error> {
+    let mut statement = connection.prepare_cached(
+        format!("
+            SELECT
+                p.id,
+                p.corretora_id,
+                c.nome,
+                p.papel,
+                p.tipo,
+                p.data_ex,
+                p.data_pagamento,
+                p.valor_bruto,
+                p.imposto_retido,
+                p.valor_liquido
+            FROM provento p
+                INNER JOIN corretora c ON
+                    p.corretora_id = c.id
+            WHERE
+                :broker_id IS NULL OR p.corretora_id = :broker_id
+            ORDER BY p.data_pagamento {}, p.id DESC
+            LIMIT :size OFFSET :offset",
+            page_request.direction
+        ).as_str()
+    )?;
+
+    let offset = page_request.page * page_request.size;
+
+    let income_rows = statement.query_map(
+        named_params! {
+            ":offset": &offset,
+            ":size": page_request.size,
+            ":broker_id": broker_id
+        },
+        INCOME_MAPPER
+    )?;
+
+    Ok(income_rows.to_vec())
+}
+
+pub(crate) fn select_income_by_year(connection: &rusqlite::Connection, year: &i32) -> Result<Vec<Income>, Error> {
+    let mut statement = connection.prepare_cached(
+    "SELECT
+            p.id,
+            p.corretora_id,
+            c.nome,
+            p.papel,
+            p.tipo,
+            p.data_ex,
+            p.data_pagamento,
+            p.valor_bruto,
+            p.imposto_retido,
+            p.valor_liquido
+        FROM provento p
+            INNER JOIN corretora c ON
+                p.corretora_id = c.id
+        WHERE SUBSTR(p.data_pagamento, 1, 4) = :year
+        ORDER BY p.data_pagamento ASC, p.id ASC"
+    )?;
+
+    let income_rows = statement.query_map(named_params! { ":year": year.to_string() }, INCOME_MAPPER)?;
+
+    Ok(income_rows.to_vec())
+}
+
+pub(crate) fn count_total_income(connection: &rusqlite::Connection, broker_id: &Option<i64>) -> Result<i64, Error> {
+    connection.query_row(
+        "SELECT COUNT(p.id) FROM provento p WHERE :broker_id IS NULL OR p.corretora_id = :broker_id",
+        named_params! { ":broker_id": broker_id },
+        |row| row.get(0)
+    ).map_err(Error::from)
+}
+
+pub(crate) fn select_income_by_id(connection: &rusqlite::Connection, income_id: &i64) -> Result<Income, Error> {
+    let mut statement = connection.prepare_cached(
+    "SELECT
+            p.id,
+            p.corretora_id,
+            c.nome,
+            p.papel,
+            p.tipo,
+            p.data_ex,
+            p.data_pagamento,
+            p.valor_bruto,
+            p.imposto_retido,
+            p.valor_liquido
+        FROM provento p
+            INNER JOIN corretora c ON
+                p.corretora_id = c.id
+        WHERE p.id = :id"
+    )?;
+
+    statement.query_row(named_params! { ":id": income_id }, INCOME_MAPPER)
+        .map_err(Error::from)
+}
+
+pub(crate) fn insert_income(connection: &rusqlite::Connection, income: &Income) -> Result<i64, Error> {
+    let mut statement = connection.prepare_cached(
+        "INSERT INTO provento (
+            corretora_id,
+            papel,
+            tipo,
+            data_ex,
+            data_pagamento,
+            valor_bruto,
+            imposto_retido,
+            valor_liquido
+        ) VALUES (
+            :broker_id,
+            :symbol,
+            :income_type,
+            :ex_date,
+            :payment_date,
+            :gross_amount,
+            :withholding_tax,
+            :net_amount
+        )"
+    )?;
+
+    statement.insert(named_params! {
+        ":broker_id": income.broker.id,
+        ":symbol": income.symbol,
+        ":income_type": income.income_type,
+        ":ex_date": income.ex_date,
+        ":payment_date": income.payment_date,
+        ":gross_amount": income.gross_amount,
+        ":withholding_tax": income.withholding_tax,
+        ":net_amount": income.net_amount
+    }).map_err(Error::from)
+}
+
+pub(crate) fn update_income(connection: &rusqlite::Connection, income: &Income) -> Result<(), Error> {
+    let mut statement = connection.prepare_cached(
+        "UPDATE provento SET
+            corretora_id = :broker_id,
+            papel = :symbol,
+            tipo = :income_type,
+            data_ex = :ex_date,
+            data_pagamento = :payment_date,
+            valor_bruto = :gross_amount,
+            imposto_retido = :withholding_tax,
+            valor_liquido = :net_amount
+        WHERE id = :id"
+    )?;
+
+    statement.execute(named_params! {
+        ":broker_id": income.broker.id,
+        ":symbol": income.symbol,
+        ":income_type": income.income_type,
+        ":ex_date": income.ex_date,
+        ":payment_date": income.payment_date,
+        ":gross_amount": income.gross_amount,
+        ":withholding_tax": income.withholding_tax,
+        ":net_amount": income.net_amount,
+        ":id": income.id
+    })?;
+
+    Ok(())
+}
+
+pub(crate) fn delete_income(connection: &rusqlite::Connection, income_id: &i64) -> Result<(), Error> {
+    connection.execute("DELETE FROM provento WHERE id = :id", named_params! { ":id": income_id })?;
+
+    Ok(())
+}
+
+pub(crate) fn exists_income(connection: &rusqlite::Connection, income: &Income) -> Result<bool, Error> {
+    let count: i64 = connection.query_row(
+        "SELECT
+            COUNT(id)
+        FROM provento
+        WHERE
+            corretora_id = :broker_id AND
+            papel = :symbol AND
+            tipo = :income_type AND
+            data_pagamento = :payment_date AND
+            ABS(valor_bruto - :gross_amount) < 0.005",
+        named_params! {
+            ":broker_id": income.broker.id,
+            ":symbol": income.symbol,
+            ":income_type": income.income_type,
+            ":payment_date": income.payment_date,
+            ":gross_amount": income.gross_amount
+        },
+        |row| row.get(0)
+    )?;
+
+    Ok(count > 0)
+}
@@ -0,0 +1,156 @@
+use chrono::NaiveDate;
+use rusqlite::types::{FromSql, FromSqlError, FromSqlResult, ToSql, ToSqlOutput, ValueRef};
+use serde::{Deserialize, Serialize};
+
+use crate::broker::Broker;
+use crate::error::{FieldError, MUST_BE_POSITIVE, MUST_NOT_BE_NEGATIVE, REQUIRED};
+
+pub(crate) const INTEREST_ON_EQUITY_WITHHOLDING_RATE: f64 = 0.15;
+
+const WITHHOLDING_TAX_ABOVE_GROSS_AMOUNT: &str = "WITHHOLDING_TAX_ABOVE_GROSS_AMOUNT";
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub(crate) enum IncomeType {
+    Dividend,
+    InterestOnEquity,
+    RealEstateFundIncome,
+    Other,
+}
+
+impl IncomeType {
+    fn as_str(&self) -> &'static str {
+        match self {
+            IncomeType::Dividend => "DIVIDEND",
+            IncomeType::InterestOnEquity => "INTEREST_ON_EQUITY",
+            IncomeType::RealEstateFundIncome => "REAL_ESTATE_FUND_INCOME",
+            IncomeType::Other => "OTHER",
+        }
+    }
+}
+
+impl ToSql for IncomeType {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(ToSqlOutput::from(self.as_str()))
+    }
+}
+
+impl FromSql for IncomeType {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        let value = value.as_str()?;
+
+        [
+            IncomeType::Dividend,
+            IncomeType::InterestOnEquity,
+            IncomeType::RealEstateFundIncome,
+            IncomeType::Other,
+        ]
+            .iter()
+            .find(|income_type| income_type.as_str() == value)
+            .copied()
+            .ok_or(FromSqlError::InvalidType)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub(crate) struct Income {
+    #[serde(default)]
+    pub(crate) id: i64,
+    pub(crate) broker: Broker,
+    pub(crate) symbol: String,
+    pub(crate) income_type: IncomeType,
+    pub(crate) ex_date: NaiveDate,
+    pub(crate) payment_date: NaiveDate,
+    pub(crate) gross_amount: f64,
+    pub(crate) withholding_tax: Option<f64>,
+    #[serde(default)]
+    pub(crate) net_amount: f64,
+}
+
+impl Income {
+    pub(crate) fn validate(&self) -> Vec<FieldError> {
+        let mut errors = Vec::new();
+
+        if self.symbol.trim().is_empty() {
+            errors.push(FieldError::new("symbol", REQUIRED));
+        }
+
+        if self.gross_amount.is_nan() || self.gross_amount <= 0.0 {
+            errors.push(FieldError::new("gross_amount", MUST_BE_POSITIVE));
+        }
+
+        match self.withholding_tax {
+            Some(withholding_tax) if withholding_tax.is_nan() || withholding_tax < 0.0 => {
+                errors.push(FieldError::new("withholding_tax", MUST_NOT_BE_NEGATIVE));
+            }
+            Some(withholding_tax) if withholding_tax > self.gross_amount => {
+                errors.push(FieldError::new("withholding_tax", WITHHOLDING_TAX_ABOVE_GROSS_AMOUNT));
+            }
+            _ => {}
+        }
+
+        errors
+    }
+
+    // Dividends and FII income are exempt; JCP has 15% withheld unless told otherwise
+    pub(crate) fn calc(&mut self) {
+        let withholding_tax = match self.income_type {
+            IncomeType::Dividend | IncomeType::RealEstateFundIncome => 0.0,
+            IncomeType::InterestOnEquity => self.withholding_tax.unwrap_or(self.gross_amount * INTEREST_ON_EQUITY_WITHHOLDING_RATE),
+            IncomeType::Other => self.withholding_tax.unwrap_or(0.0),
+        };
+
+        self.withholding_tax = Some(withholding_tax);
+        self.net_amount = self.gross_amount - withholding_tax;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn income(income_type: IncomeType, gross_amount: f64, withholding_tax: Option<f64>) -> Income {
+        Income {
+            id: 0,
+            broker: Broker {
+                id: 1,
+                name: String::new(),
+            },
+            symbol: "ITSA4".to_string(),
+            income_type,
+            ex_date: NaiveDate::from_ymd(2021, 3, 1),
+            payment_date: NaiveDate::from_ymd(2021, 3, 31),
+            gross_amount,
+            withholding_tax,
+            net_amount: 0.0,
+        }
+    }
+
+    fn fields(errors: &[FieldError]) -> Vec<(&str, &str)> {
+        errors.iter().map(|error| (error.field.as_str(), error.code.as_str())).collect()
+    }
+
+    #[test]
+    fn rejects_invalid_amounts() {
+        assert!(income(IncomeType::Dividend, 100.0, None).validate().is_empty());
+
+        assert_eq!(fields(&income(IncomeType::Dividend, f64::NAN, None).validate()), vec![("gross_amount", MUST_BE_POSITIVE)]);
+        assert_eq!(fields(&income(IncomeType::Dividend, -10.0, None).validate()), vec![("gross_amount", MUST_BE_POSITIVE)]);
+        assert_eq!(
+            fields(&income(IncomeType::InterestOnEquity, 100.0, Some(-1.0)).validate()),
+            vec![("withholding_tax", MUST_NOT_BE_NEGATIVE)]
+        );
+        assert_eq!(
+            fields(&income(IncomeType::InterestOnEquity, 100.0, Some(150.0)).validate()),
+            vec![("withholding_tax", WITHHOLDING_TAX_ABOVE_GROSS_AMOUNT)]
+        );
+    }
+
+    #[test]
+    fn interest_on_equity_withholds_tax_by_default() {
+        let mut interest_on_equity = income(IncomeType::InterestOnEquity, 100.0, None);
+        interest_on_equity.calc();
+
+        assert_eq!((interest_on_equity.withholding_tax, interest_on_equity.net_amount), (Some(15.0), 85.0));
+    }
+}
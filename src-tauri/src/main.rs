@@ -5,22 +5,69 @@ windows_subsystem = "windows"
 
 pub(crate) mod database;
 pub(crate) mod config;
+mod asset;
 mod broker;
 mod brokerage_note;
 mod commons;
+mod corporate_event;
+mod darf;
+mod error;
+mod import;
+mod income;
+mod position;
+mod report;
+mod tax;
 
 fn main() {
     tauri::Builder::default()
+        .manage(database::Database::open())
         .invoke_handler(tauri::generate_handler![
         database::create_database,
+        database::get_orphan_rows,
         broker::get_broker_list,
         broker::new_broker,
         broker::update_broker,
+        broker::delete_broker,
         brokerage_note::get_brokerage_note_page,
         brokerage_note::get_brokerage_note,
         brokerage_note::new_brokerage_note,
         brokerage_note::update_brokerage_note,
-        brokerage_note::delete_brokerage_note
+        brokerage_note::delete_brokerage_note,
+        import::preview_brokerage_note_pdf,
+        import::preview_b3_trades,
+        import::import_brokerage_notes,
+        import::import_b3_trades,
+        import::import_b3_movements,
+        position::get_positions,
+        position::get_realized_results,
+        position::get_day_trade_results,
+        position::get_corporate_event_results,
+        position::get_uncovered_sales,
+        tax::get_monthly_tax,
+        tax::get_accumulated_losses,
+        darf::get_pending_darfs,
+        darf::get_darfs,
+        darf::pay_darf,
+        darf::import_selic_rates,
+        darf::calc_late_darf,
+        asset::get_asset_list,
+        asset::new_asset,
+        asset::update_asset,
+        asset::delete_asset,
+        asset::import_instruments,
+        asset::get_unknown_symbols,
+        corporate_event::get_corporate_events,
+        corporate_event::new_corporate_event,
+        corporate_event::update_corporate_event,
+        corporate_event::delete_corporate_event,
+        income::get_income_page,
+        income::get_income,
+        income::new_income,
+        income::update_income,
+        income::delete_income,
+        report::get_assets_and_rights_report,
+        report::export_assets_and_rights_report,
+        report::get_income_report
     ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
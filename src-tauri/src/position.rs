@@ -0,0 +1,462 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Local, NaiveDate};
+use tauri::{InvokeError, State};
+
+use crate::broker::Broker;
+use crate::corporate_event;
+use crate::corporate_event::types::{CorporateEvent, CorporateEventType};
+use crate::database::Database;
+use crate::error::Error;
+use crate::tax;
+use crate::position::types::{CorporateEventResult, DayTradeResult, Position, PositionOrder, RealizedResult, Replay, TradeMatch, UncoveredSale};
+
+pub(crate) mod db;
+pub(crate) mod types;
+
+#[tauri::command(async)]
+pub(crate) fn get_positions(database: State<'_, Database>, broker_id: Option<i64>, date: Option<DateTime<Local>>) -> Result<Vec<Position>, InvokeError> {
+    let connection = database.connection()?;
+
+    Ok(select_positions(&connection, broker_id, date.map(|date| date.naive_local().date()))?)
+}
+
+pub(crate) fn select_positions(connection: &rusqlite::Connection, broker_id: Option<i64>, date: Option<NaiveDate>) -> Result<Vec<Position>, Error> {
+    let orders: Vec<PositionOrder> = db::select_position_orders(connection)?
+        .into_iter()
+        .filter(|order| date.map_or(true, |date| order.trading_date.naive_local().date() <= date))
+        .collect();
+
+    let corporate_events: Vec<CorporateEvent> = corporate_event::db::select_corporate_events(connection, &None)?
+        .into_iter()
+        .filter(|corporate_event| date.map_or(true, |date| corporate_event.ex_date <= date))
+        .collect();
+
+    let broker_id = match broker_id {
+        Some(broker_id) => broker_id,
+        None => return Ok(replay(&orders, &corporate_events).book.into_positions()),
+    };
+
+    let corporate_events = proportional_events(&orders, corporate_events);
+    let orders: Vec<PositionOrder> = orders
+        .into_iter()
+        .filter(|order| order.broker_id == broker_id)
+        .collect();
+
+    Ok(replay(&orders, &corporate_events).book.into_positions())
+}
+
+// Splits and bonus shares imported from B3 carry the amount credited across every broker, so
+// for a single broker's book that amount is turned into the ratio it represents for the whole
+// position held on the ex-date.
+fn proportional_events(orders: &[PositionOrder], corporate_events: Vec<CorporateEvent>) -> Vec<CorporateEvent> {
+    let held_amounts: Vec<i64> = corporate_events.iter()
+        .enumerate()
+        .map(|(index, corporate_event)| {
+            let end = orders.partition_point(|order| order.trading_date.naive_local().date() < corporate_event.ex_date);
+
+            replay(&orders[..end], &corporate_events[..index]).book.amount(&corporate_event.symbol)
+        })
+        .collect();
+
+    corporate_events
+        .into_iter()
+        .zip(held_amounts)
+        .map(|(corporate_event, held_amount)| match (corporate_event.event_type, corporate_event.amount) {
+            (CorporateEventType::Split, Some(amount)) if held_amount > 0 => CorporateEvent {
+                ratio_from: held_amount as f64,
+                ratio_to: (held_amount + amount) as f64,
+                amount: None,
+                ..corporate_event
+            },
+            (CorporateEventType::Bonus, Some(amount)) if held_amount > 0 => CorporateEvent {
+                ratio_from: held_amount as f64,
+                ratio_to: amount as f64,
+                amount: None,
+                ..corporate_event
+            },
+            _ => corporate_event,
+        })
+        .collect()
+}
+
+#[tauri::command(async)]
+pub(crate) fn get_realized_results(database: State<'_, Database>, month: Option<String>, symbol: Option<String>, broker_id: Option<i64>) -> Result<Vec<RealizedResult>, InvokeError> {
+    let connection = database.connection()?;
+
+    Ok(db::select_realized_results(&connection, &month, &symbol, &broker_id)?)
+}
+
+#[tauri::command(async)]
+pub(crate) fn get_day_trade_results(database: State<'_, Database>, month: Option<String>, symbol: Option<String>, broker_id: Option<i64>) -> Result<Vec<DayTradeResult>, InvokeError> {
+    let connection = database.connection()?;
+
+    Ok(db::select_day_trade_results(&connection, &month, &symbol, &broker_id)?)
+}
+
+#[tauri::command(async)]
+pub(crate) fn get_corporate_event_results(database: State<'_, Database>, month: Option<String>, symbol: Option<String>) -> Result<Vec<CorporateEventResult>, InvokeError> {
+    let connection = database.connection()?;
+
+    Ok(db::select_corporate_event_results(&connection, &month, &symbol)?)
+}
+
+#[tauri::command(async)]
+pub(crate) fn get_uncovered_sales(database: State<'_, Database>) -> Result<Vec<UncoveredSale>, InvokeError> {
+    let connection = database.connection()?;
+
+    let orders = db::select_position_orders(&connection)?;
+    let corporate_events = corporate_event::db::select_corporate_events(&connection, &None)?;
+
+    Ok(replay(&orders, &corporate_events).uncovered_sales)
+}
+
+pub(crate) fn update_realized_results(transaction: &rusqlite::Transaction) -> Result<(), Error> {
+    let orders = db::select_position_orders(transaction)?;
+    let corporate_events = corporate_event::db::select_corporate_events(transaction, &None)?;
+
+    let replay = replay(&orders, &corporate_events);
+
+    for uncovered_sale in &replay.uncovered_sales {
+        println!("Venda de {} {} sem posição (ordem {}), deixada fora do resultado", uncovered_sale.amount, uncovered_sale.symbol, uncovered_sale.order_id);
+    }
+
+    db::delete_realized_results(transaction)?;
+    db::insert_realized_results(transaction, &replay.realized_results)?;
+
+    db::delete_day_trade_results(transaction)?;
+    db::insert_day_trade_results(transaction, &replay.day_trade_results)?;
+
+    db::delete_corporate_event_results(transaction)?;
+    db::insert_corporate_event_results(transaction, &replay.corporate_event_results)?;
+
+    tax::update_tax_records(transaction)
+}
+
+pub(crate) fn match_trades<'a>(orders: impl Iterator<Item = (&'a str, &'a str, i64)>) -> Vec<TradeMatch> {
+    let mut amounts: BTreeMap<&str, (i64, i64)> = BTreeMap::new();
+
+    for (symbol, order_type, amount) in orders {
+        let (purchased, sold) = amounts.entry(symbol).or_insert((0, 0));
+        match order_type {
+            "C" => *purchased += amount,
+            "V" => *sold += amount,
+            _ => {}
+        }
+    }
+
+    amounts
+        .into_iter()
+        .map(|(symbol, (purchased_amount, sold_amount))| {
+            let day_trade_amount = purchased_amount.min(sold_amount);
+
+            TradeMatch {
+                symbol: symbol.to_string(),
+                purchased_amount,
+                sold_amount,
+                day_trade_amount,
+                swing_trade_amount: purchased_amount + sold_amount - 2 * day_trade_amount,
+            }
+        })
+        .collect()
+}
+
+// Corporate events take effect before the orders of their ex-date, which already trade with
+// the adjusted amounts.
+pub(crate) fn replay(orders: &[PositionOrder], corporate_events: &[CorporateEvent]) -> Replay {
+    let mut replay = Replay::default();
+    let mut pending_events = corporate_events.iter().peekable();
+
+    let mut start = 0;
+    while start < orders.len() {
+        let trading_date = orders[start].trading_date.date();
+        let end = orders[start..]
+            .iter()
+            .position(|order| order.trading_date.date() != trading_date)
+            .map_or(orders.len(), |length| start + length);
+
+        while let Some(corporate_event) = pending_events.next_if(|event| event.ex_date <= trading_date.naive_local()) {
+            apply_corporate_event(&mut replay, corporate_event);
+        }
+
+        replay_trading_day(&mut replay, &orders[start..end]);
+        start = end;
+    }
+
+    for corporate_event in pending_events {
+        apply_corporate_event(&mut replay, corporate_event);
+    }
+
+    replay
+}
+
+fn apply_corporate_event(replay: &mut Replay, corporate_event: &CorporateEvent) {
+    if let Some(corporate_event_result) = corporate_event.apply(&mut replay.book) {
+        replay.corporate_event_results.push(corporate_event_result);
+    }
+}
+
+struct DayTrade<'a> {
+    first_order: &'a PositionOrder,
+    amount: i64,
+    remaining_purchase: i64,
+    remaining_sale: i64,
+    cost_basis: f64,
+    proceeds: f64,
+}
+
+// Purchases and sales of the same symbol at the same broker within one trading day are day
+// trades; only what is left over moves the swing trade position and its average price.
+fn replay_trading_day(replay: &mut Replay, orders: &[PositionOrder]) {
+    let mut day_trades: BTreeMap<(i64, &str), DayTrade> = BTreeMap::new();
+
+    let mut orders_by_broker: BTreeMap<i64, Vec<&PositionOrder>> = BTreeMap::new();
+    for order in orders {
+        orders_by_broker.entry(order.broker_id).or_insert_with(Vec::new).push(order);
+    }
+
+    for (broker_id, broker_orders) in orders_by_broker {
+        let trade_matches = match_trades(
+            broker_orders.iter().map(|order| (order.symbol.as_str(), order.order_type.as_str(), order.amount))
+        );
+
+        for trade_match in trade_matches.iter().filter(|trade_match| trade_match.day_trade_amount > 0) {
+            let first_order = broker_orders.iter()
+                .find(|order| order.symbol == trade_match.symbol)
+                .unwrap();
+
+            day_trades.insert((broker_id, first_order.symbol.as_str()), DayTrade {
+                first_order,
+                amount: trade_match.day_trade_amount,
+                remaining_purchase: trade_match.day_trade_amount,
+                remaining_sale: trade_match.day_trade_amount,
+                cost_basis: 0.0,
+                proceeds: 0.0,
+            });
+        }
+    }
+
+    for order in orders {
+        let ratio = |amount: i64| amount as f64 / order.amount as f64;
+        let day_trade = day_trades.get_mut(&(order.broker_id, order.symbol.as_str()));
+
+        match order.order_type.as_str() {
+            "C" => {
+                let day_trade_amount = match day_trade {
+                    Some(day_trade) => {
+                        let day_trade_amount = day_trade.remaining_purchase.min(order.amount);
+                        day_trade.remaining_purchase -= day_trade_amount;
+                        day_trade.cost_basis += (order.order_value + order.fees) * ratio(day_trade_amount);
+                        day_trade_amount
+                    }
+                    None => 0,
+                };
+
+                let swing_trade_amount = order.amount - day_trade_amount;
+                if swing_trade_amount > 0 {
+                    replay.book.buy(&order.symbol, swing_trade_amount, (order.order_value + order.fees) * ratio(swing_trade_amount));
+                }
+            }
+            "V" => {
+                let day_trade_amount = match day_trade {
+                    Some(day_trade) => {
+                        let day_trade_amount = day_trade.remaining_sale.min(order.amount);
+                        day_trade.remaining_sale -= day_trade_amount;
+                        day_trade.proceeds += (order.order_value - order.fees) * ratio(day_trade_amount);
+                        day_trade_amount
+                    }
+                    None => 0,
+                };
+
+                let sold_amount = order.amount - day_trade_amount;
+                let swing_trade_amount = sold_amount.min(replay.book.amount(&order.symbol));
+
+                if sold_amount > swing_trade_amount {
+                    replay.uncovered_sales.push(UncoveredSale {
+                        order_id: order.id,
+                        broker: Broker {
+                            id: order.broker_id,
+                            name: order.broker_name.clone(),
+                        },
+                        trading_date: order.trading_date,
+                        symbol: order.symbol.clone(),
+                        amount: sold_amount - swing_trade_amount,
+                    });
+                }
+
+                if swing_trade_amount > 0 {
+                    let average_price = replay.book.sell(&order.symbol, swing_trade_amount);
+                    let cost_basis = average_price * swing_trade_amount as f64;
+                    let proceeds = (order.order_value - order.fees) * ratio(swing_trade_amount);
+
+                    replay.realized_results.push(RealizedResult {
+                        id: 0,
+                        order_id: order.id,
+                        broker: Broker {
+                            id: order.broker_id,
+                            name: order.broker_name.clone(),
+                        },
+                        trading_date: order.trading_date,
+                        symbol: order.symbol.clone(),
+                        amount: swing_trade_amount,
+                        average_price,
+                        cost_basis,
+                        proceeds,
+                        result: proceeds - cost_basis,
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+
+    for (_, day_trade) in day_trades {
+        replay.day_trade_results.push(DayTradeResult {
+            id: 0,
+            broker: Broker {
+                id: day_trade.first_order.broker_id,
+                name: day_trade.first_order.broker_name.clone(),
+            },
+            trading_date: day_trade.first_order.trading_date,
+            symbol: day_trade.first_order.symbol.clone(),
+            amount: day_trade.amount,
+            purchase_price: day_trade.cost_basis / day_trade.amount as f64,
+            sale_price: day_trade.proceeds / day_trade.amount as f64,
+            cost_basis: day_trade.cost_basis,
+            proceeds: day_trade.proceeds,
+            result: day_trade.proceeds - day_trade.cost_basis,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    fn order(id: i64, broker_id: i64, day: u32, order_type: &str, symbol: &str, amount: i64, order_value: f64) -> PositionOrder {
+        PositionOrder {
+            id,
+            broker_id,
+            broker_name: format!("Corretora {}", broker_id),
+            trading_date: Local.ymd(2021, 3, day).and_hms(0, 0, 0),
+            order_type: order_type.to_string(),
+            symbol: symbol.to_string(),
+            amount,
+            order_value,
+            fees: 0.0,
+        }
+    }
+
+    #[test]
+    fn credited_split_amount_is_shared_between_brokers() {
+        let orders = vec![
+            order(1, 1, 1, "C", "PETR4", 100, 2000.0),
+            order(2, 2, 2, "C", "PETR4", 300, 6000.0),
+        ];
+        let split = CorporateEvent {
+            id: 1,
+            symbol: "PETR4".to_string(),
+            event_type: CorporateEventType::Split,
+            ex_date: NaiveDate::from_ymd(2021, 3, 10),
+            ratio_from: 1.0,
+            ratio_to: 1.0,
+            unit_cost: None,
+            cash_amount: None,
+            amount: Some(400),
+            targets: Vec::new(),
+        };
+
+        let corporate_events = proportional_events(&orders, vec![split]);
+        assert_eq!((corporate_events[0].ratio_from, corporate_events[0].ratio_to, corporate_events[0].amount), (400.0, 800.0, None));
+
+        let broker_orders: Vec<PositionOrder> = orders.into_iter().filter(|order| order.broker_id == 1).collect();
+        let positions = replay(&broker_orders, &corporate_events).book.into_positions();
+        assert_eq!((positions[0].amount, positions[0].total_invested), (200, 2000.0));
+    }
+
+    #[test]
+    fn matches_purchases_and_sales_per_symbol() {
+        let trade_matches = match_trades(vec![
+            ("PETR4", "C", 100),
+            ("VALE3", "V", 30),
+            ("PETR4", "V", 60),
+            ("PETR4", "C", 20),
+        ].into_iter());
+
+        assert_eq!(trade_matches.len(), 2);
+        let petr4 = &trade_matches[0];
+        assert_eq!(
+            (petr4.symbol.as_str(), petr4.purchased_amount, petr4.sold_amount, petr4.day_trade_amount, petr4.swing_trade_amount),
+            ("PETR4", 120, 60, 60, 60)
+        );
+        let vale3 = &trade_matches[1];
+        assert_eq!((vale3.day_trade_amount, vale3.swing_trade_amount), (0, 30));
+    }
+
+    #[test]
+    fn day_trade_is_kept_out_of_the_position() {
+        let orders = vec![
+            order(1, 1, 1, "C", "PETR4", 100, 2000.0),
+            order(2, 1, 2, "C", "PETR4", 100, 2200.0),
+            order(3, 1, 2, "V", "PETR4", 60, 1380.0),
+        ];
+
+        let replay = replay(&orders, &[]);
+
+        assert!(replay.realized_results.is_empty());
+        assert_eq!(replay.day_trade_results.len(), 1);
+        let day_trade_result = &replay.day_trade_results[0];
+        assert_eq!(day_trade_result.amount, 60);
+        assert!((day_trade_result.cost_basis - 1320.0).abs() < 1e-9);
+        assert!((day_trade_result.proceeds - 1380.0).abs() < 1e-9);
+        assert!((day_trade_result.result - 60.0).abs() < 1e-9);
+
+        let positions = replay.book.into_positions();
+        assert_eq!(positions[0].amount, 140);
+        assert!((positions[0].total_invested - 2880.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn orders_at_different_brokers_are_not_a_day_trade() {
+        let orders = vec![
+            order(1, 1, 1, "C", "PETR4", 100, 2000.0),
+            order(2, 1, 2, "C", "PETR4", 100, 2200.0),
+            order(3, 2, 2, "V", "PETR4", 60, 1380.0),
+        ];
+
+        let replay = replay(&orders, &[]);
+
+        assert!(replay.day_trade_results.is_empty());
+        assert_eq!(replay.realized_results.len(), 1);
+        let realized_result = &replay.realized_results[0];
+        assert_eq!((realized_result.order_id, realized_result.amount), (3, 60));
+        assert!((realized_result.cost_basis - 1260.0).abs() < 1e-9);
+        assert!((realized_result.result - 120.0).abs() < 1e-9);
+
+        let positions = replay.book.into_positions();
+        assert_eq!(positions[0].amount, 140);
+    }
+
+    #[test]
+    fn sale_beyond_position_is_reported_not_taxed() {
+        let orders = vec![
+            order(1, 1, 1, "C", "PETR4", 100, 2000.0),
+            order(2, 1, 2, "V", "PETR4", 150, 4500.0),
+            order(3, 1, 3, "C", "PETR4", 10, 300.0),
+        ];
+
+        let replay = replay(&orders, &[]);
+
+        assert_eq!(replay.realized_results.len(), 1);
+        let realized_result = &replay.realized_results[0];
+        assert_eq!((realized_result.amount, realized_result.cost_basis, realized_result.proceeds), (100, 2000.0, 3000.0));
+
+        assert_eq!(replay.uncovered_sales.len(), 1);
+        assert_eq!((replay.uncovered_sales[0].order_id, replay.uncovered_sales[0].amount), (2, 50));
+
+        let positions = replay.book.into_positions();
+        assert_eq!((positions[0].amount, positions[0].average_price), (10, 30.0));
+    }
+}
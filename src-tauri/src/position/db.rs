@@ -0,0 +1,333 @@
+use rusqlite::named_params;
+
+use crate::broker;
+use crate::commons::{QueryMapper, ToVec};
+use crate::error::Error;
+use crate::position::types::{CorporateEventResult, DayTradeResult, PositionOrder, RealizedResult};
+
+const POSITION_ORDER_MAPPER: QueryMapper<PositionOrder> = |row| {
+    Ok(PositionOrder {
+        id: row.get(0)?,
+        broker_id: row.get(1)?,
+        broker_name: row.get(2)?,
+        trading_date: row.get(3)?,
+        order_type: row.get(4)?,
+        symbol: row.get(5)?,
+        amount: row.get(6)?,
+        order_value: row.get(7)?,
+        fees: row.get(8)?,
+    })
+};
+
+const REALIZED_RESULT_MAPPER: QueryMapper<RealizedResult> = |row| {
+    Ok(RealizedResult {
+        id: row.get(0)?,
+        order_id: row.get(1)?,
+        broker: broker::Broker {
+            id: row.get(2)?,
+            name: row.get(3)?,
+        },
+        trading_date: row.get(4)?,
+        symbol: row.get(5)?,
+        amount: row.get(6)?,
+        average_price: row.get(7)?,
+        cost_basis: row.get(8)?,
+        proceeds: row.get(9)?,
+        result: row.get(10)?,
+    })
+};
+
+pub(crate) fn select_position_orders(connection: &rusqlite::Connection) -> Result<Vec<PositionOrder>, Error> {
+    let mut statement = connection.prepare_cached(
+    "SELECT
+            nco.id,
+            nc.corretora_id,
+            c.nome,
+            nc.data_pregao,
+            nco.tipo,
+            nco.papel,
+            nco.quantidade,
+            nco.valor_ordem,
+            nco.total_custo - nco.irrf
+        FROM nota_corretagem_ordem nco
+            INNER JOIN nota_corretagem nc ON
+                nco.nota_corretagem_id = nc.id
+            INNER JOIN corretora c ON
+                nc.corretora_id = c.id
+        ORDER BY nc.data_pregao ASC, nc.id ASC, nco.id ASC"
+    )?;
+
+    let order_rows = statement.query_map([], POSITION_ORDER_MAPPER)?;
+
+    Ok(order_rows.to_vec())
+}
+
+const DAY_TRADE_RESULT_MAPPER: QueryMapper<DayTradeResult> = |row| {
+    Ok(DayTradeResult {
+        id: row.get(0)?,
+        broker: broker::Broker {
+            id: row.get(1)?,
+            name: row.get(2)?,
+        },
+        trading_date: row.get(3)?,
+        symbol: row.get(4)?,
+        amount: row.get(5)?,
+        purchase_price: row.get(6)?,
+        sale_price: row.get(7)?,
+        cost_basis: row.get(8)?,
+        proceeds: row.get(9)?,
+        result: row.get(10)?,
+    })
+};
+
+pub(crate) fn select_realized_results(connection: &rusqlite::Connection, month: &Option<String>, symbol: &Option<String>, broker_id: &Option<i64>) -> Result<Vec<RealizedResult>, Error> {
+    let mut statement = connection.prepare_cached(
+    "SELECT
+            ro.id,
+            ro.nota_corretagem_ordem_id,
+            ro.corretora_id,
+            c.nome,
+            ro.data_pregao,
+            ro.papel,
+            ro.quantidade,
+            ro.preco_medio,
+            ro.custo_aquisicao,
+            ro.valor_venda,
+            ro.resultado
+        FROM resultado_operacao ro
+            INNER JOIN corretora c ON
+                ro.corretora_id = c.id
+        WHERE
+            (:month IS NULL OR STRFTIME('%Y-%m', ro.data_pregao) = :month) AND
+            (:symbol IS NULL OR ro.papel = :symbol) AND
+            (:broker_id IS NULL OR ro.corretora_id = :broker_id)
+        ORDER BY ro.data_pregao ASC, ro.id ASC"
+    )?;
+
+    let result_rows = statement.query_map(
+        named_params! {
+            ":month": month,
+            ":symbol": symbol,
+            ":broker_id": broker_id
+        },
+        REALIZED_RESULT_MAPPER
+    )?;
+
+    Ok(result_rows.to_vec())
+}
+
+pub(crate) fn delete_realized_results(transaction: &rusqlite::Transaction) -> Result<(), Error> {
+    transaction.execute("DELETE FROM resultado_operacao", [])?;
+
+    Ok(())
+}
+
+pub(crate) fn insert_realized_results(transaction: &rusqlite::Transaction, realized_results: &Vec<RealizedResult>) -> Result<(), Error> {
+    let mut statement = transaction.prepare_cached(
+        "INSERT INTO resultado_operacao (
+            nota_corretagem_ordem_id,
+            corretora_id,
+            data_pregao,
+            papel,
+            quantidade,
+            preco_medio,
+            custo_aquisicao,
+            valor_venda,
+            resultado
+        ) VALUES (
+            :order_id,
+            :broker_id,
+            :trading_date,
+            :symbol,
+            :amount,
+            :average_price,
+            :cost_basis,
+            :proceeds,
+            :result
+        )"
+    )?;
+
+    for realized_result in realized_results {
+        statement.insert(named_params! {
+            ":order_id": realized_result.order_id,
+            ":broker_id": realized_result.broker.id,
+            ":trading_date": realized_result.trading_date,
+            ":symbol": realized_result.symbol,
+            ":amount": realized_result.amount,
+            ":average_price": realized_result.average_price,
+            ":cost_basis": realized_result.cost_basis,
+            ":proceeds": realized_result.proceeds,
+            ":result": realized_result.result
+        })?;
+    }
+
+    Ok(())
+}
+
+pub(crate) fn select_day_trade_results(connection: &rusqlite::Connection, month: &Option<String>, symbol: &Option<String>, broker_id: &Option<i64>) -> Result<Vec<DayTradeResult>, Error> {
+    let mut statement = connection.prepare_cached(
+    "SELECT
+            rdt.id,
+            rdt.corretora_id,
+            c.nome,
+            rdt.data_pregao,
+            rdt.papel,
+            rdt.quantidade,
+            rdt.preco_medio_compra,
+            rdt.preco_medio_venda,
+            rdt.custo_aquisicao,
+            rdt.valor_venda,
+            rdt.resultado
+        FROM resultado_day_trade rdt
+            INNER JOIN corretora c ON
+                rdt.corretora_id = c.id
+        WHERE
+            (:month IS NULL OR STRFTIME('%Y-%m', rdt.data_pregao) = :month) AND
+            (:symbol IS NULL OR rdt.papel = :symbol) AND
+            (:broker_id IS NULL OR rdt.corretora_id = :broker_id)
+        ORDER BY rdt.data_pregao ASC, rdt.id ASC"
+    )?;
+
+    let result_rows = statement.query_map(
+        named_params! {
+            ":month": month,
+            ":symbol": symbol,
+            ":broker_id": broker_id
+        },
+        DAY_TRADE_RESULT_MAPPER
+    )?;
+
+    Ok(result_rows.to_vec())
+}
+
+pub(crate) fn delete_day_trade_results(transaction: &rusqlite::Transaction) -> Result<(), Error> {
+    transaction.execute("DELETE FROM resultado_day_trade", [])?;
+
+    Ok(())
+}
+
+pub(crate) fn insert_day_trade_results(transaction: &rusqlite::Transaction, day_trade_results: &Vec<DayTradeResult>) -> Result<(), Error> {
+    let mut statement = transaction.prepare_cached(
+        "INSERT INTO resultado_day_trade (
+            corretora_id,
+            data_pregao,
+            papel,
+            quantidade,
+            preco_medio_compra,
+            preco_medio_venda,
+            custo_aquisicao,
+            valor_venda,
+            resultado
+        ) VALUES (
+            :broker_id,
+            :trading_date,
+            :symbol,
+            :amount,
+            :purchase_price,
+            :sale_price,
+            :cost_basis,
+            :proceeds,
+            :result
+        )"
+    )?;
+
+    for day_trade_result in day_trade_results {
+        statement.insert(named_params! {
+            ":broker_id": day_trade_result.broker.id,
+            ":trading_date": day_trade_result.trading_date,
+            ":symbol": day_trade_result.symbol,
+            ":amount": day_trade_result.amount,
+            ":purchase_price": day_trade_result.purchase_price,
+            ":sale_price": day_trade_result.sale_price,
+            ":cost_basis": day_trade_result.cost_basis,
+            ":proceeds": day_trade_result.proceeds,
+            ":result": day_trade_result.result
+        })?;
+    }
+
+    Ok(())
+}
+
+const CORPORATE_EVENT_RESULT_MAPPER: QueryMapper<CorporateEventResult> = |row| {
+    Ok(CorporateEventResult {
+        id: row.get(0)?,
+        corporate_event_id: row.get(1)?,
+        ex_date: row.get(2)?,
+        symbol: row.get(3)?,
+        amount: row.get(4)?,
+        cost_basis: row.get(5)?,
+        proceeds: row.get(6)?,
+        result: row.get(7)?,
+    })
+};
+
+pub(crate) fn select_corporate_event_results(connection: &rusqlite::Connection, month: &Option<String>, symbol: &Option<String>) -> Result<Vec<CorporateEventResult>, Error> {
+    let mut statement = connection.prepare_cached(
+    "SELECT
+            id,
+            evento_corporativo_id,
+            data_ex,
+            papel,
+            quantidade,
+            custo_aquisicao,
+            valor_recebido,
+            resultado
+        FROM resultado_evento_corporativo
+        WHERE
+            (:month IS NULL OR STRFTIME('%Y-%m', data_ex) = :month) AND
+            (:symbol IS NULL OR papel = :symbol)
+        ORDER BY data_ex ASC, id ASC"
+    )?;
+
+    let result_rows = statement.query_map(
+        named_params! {
+            ":month": month,
+            ":symbol": symbol
+        },
+        CORPORATE_EVENT_RESULT_MAPPER
+    )?;
+
+    Ok(result_rows.to_vec())
+}
+
+pub(crate) fn delete_corporate_event_results(transaction: &rusqlite::Transaction) -> Result<(), Error> {
+    transaction.execute("DELETE FROM resultado_evento_corporativo", [])?;
+
+    Ok(())
+}
+
+pub(crate) fn insert_corporate_event_results(transaction: &rusqlite::Transaction, corporate_event_results: &Vec<CorporateEventResult>) -> Result<(), Error> {
+    let mut statement = transaction.prepare_cached(
+        "INSERT INTO resultado_evento_corporativo (
+            evento_corporativo_id,
+            data_ex,
+            papel,
+            quantidade,
+            custo_aquisicao,
+            valor_recebido,
+            resultado
+        ) VALUES (
+            :corporate_event_id,
+            :ex_date,
+            :symbol,
+            :amount,
+            :cost_basis,
+            :proceeds,
+            :result
+        )"
+    )?;
+
+    for corporate_event_result in corporate_event_results {
+        statement.insert(named_params! {
+            ":corporate_event_id": corporate_event_result.corporate_event_id,
+            ":ex_date": corporate_event_result.ex_date,
+            ":symbol": corporate_event_result.symbol,
+            ":amount": corporate_event_result.amount,
+            ":cost_basis": corporate_event_result.cost_basis,
+            ":proceeds": corporate_event_result.proceeds,
+            ":result": corporate_event_result.result
+        })?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,230 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Local, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+use crate::broker::Broker;
+
+#[derive(Serialize, Deserialize, Debug)]
+pub(crate) struct Position {
+    pub(crate) symbol: String,
+    pub(crate) amount: i64,
+    pub(crate) average_price: f64,
+    pub(crate) total_invested: f64,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub(crate) struct RealizedResult {
+    #[serde(default)]
+    pub(crate) id: i64,
+    pub(crate) order_id: i64,
+    pub(crate) broker: Broker,
+    pub(crate) trading_date: DateTime<Local>,
+    pub(crate) symbol: String,
+    pub(crate) amount: i64,
+    pub(crate) average_price: f64,
+    pub(crate) cost_basis: f64,
+    pub(crate) proceeds: f64,
+    pub(crate) result: f64,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub(crate) struct DayTradeResult {
+    #[serde(default)]
+    pub(crate) id: i64,
+    pub(crate) broker: Broker,
+    pub(crate) trading_date: DateTime<Local>,
+    pub(crate) symbol: String,
+    pub(crate) amount: i64,
+    pub(crate) purchase_price: f64,
+    pub(crate) sale_price: f64,
+    pub(crate) cost_basis: f64,
+    pub(crate) proceeds: f64,
+    pub(crate) result: f64,
+}
+
+// Cash received in a merger, spin-off or ticker change above the whole cost basis of the source
+#[derive(Serialize, Deserialize, Debug)]
+pub(crate) struct CorporateEventResult {
+    #[serde(default)]
+    pub(crate) id: i64,
+    pub(crate) corporate_event_id: i64,
+    pub(crate) ex_date: NaiveDate,
+    pub(crate) symbol: String,
+    pub(crate) amount: i64,
+    pub(crate) cost_basis: f64,
+    pub(crate) proceeds: f64,
+    pub(crate) result: f64,
+}
+
+// Part of a sale beyond the position held, e.g. when the purchase was never recorded. It has no
+// known cost, so it is left out of the realized results and reported instead.
+#[derive(Serialize, Deserialize, Debug)]
+pub(crate) struct UncoveredSale {
+    pub(crate) order_id: i64,
+    pub(crate) broker: Broker,
+    pub(crate) trading_date: DateTime<Local>,
+    pub(crate) symbol: String,
+    pub(crate) amount: i64,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub(crate) struct TradeMatch {
+    pub(crate) symbol: String,
+    pub(crate) purchased_amount: i64,
+    pub(crate) sold_amount: i64,
+    pub(crate) day_trade_amount: i64,
+    pub(crate) swing_trade_amount: i64,
+}
+
+#[derive(Debug)]
+pub(crate) struct PositionOrder {
+    pub(crate) id: i64,
+    pub(crate) broker_id: i64,
+    pub(crate) broker_name: String,
+    pub(crate) trading_date: DateTime<Local>,
+    pub(crate) order_type: String,
+    pub(crate) symbol: String,
+    pub(crate) amount: i64,
+    pub(crate) order_value: f64,
+    // Costs that go into the cost basis and proceeds. The withheld IRRF is left out, since it
+    // is credited against the tax due instead.
+    pub(crate) fees: f64,
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct Replay {
+    pub(crate) book: PositionBook,
+    pub(crate) realized_results: Vec<RealizedResult>,
+    pub(crate) day_trade_results: Vec<DayTradeResult>,
+    pub(crate) corporate_event_results: Vec<CorporateEventResult>,
+    pub(crate) uncovered_sales: Vec<UncoveredSale>,
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct PositionBook {
+    positions: BTreeMap<String, Position>,
+}
+
+impl PositionBook {
+    pub(crate) fn buy(&mut self, symbol: &str, amount: i64, cost: f64) {
+        let position = self.position(symbol);
+
+        // Whatever cost is left on an empty position, e.g. after a reverse split, is stale
+        if position.amount <= 0 {
+            position.amount = 0;
+            position.total_invested = 0.0;
+        }
+
+        position.amount += amount;
+        position.total_invested += cost;
+        position.average_price = if position.amount > 0 { position.total_invested / position.amount as f64 } else { 0.0 };
+    }
+
+    // Never goes below zero; the caller checks the amount held for sales that exceed it
+    pub(crate) fn sell(&mut self, symbol: &str, amount: i64) -> f64 {
+        let position = self.position(symbol);
+        let average_price = position.average_price;
+
+        position.amount = (position.amount - amount).max(0);
+        if position.amount > 0 {
+            position.total_invested = position.average_price * position.amount as f64;
+        } else {
+            position.total_invested = 0.0;
+            position.average_price = 0.0;
+        }
+
+        average_price
+    }
+
+    pub(crate) fn amount(&self, symbol: &str) -> i64 {
+        self.positions.get(symbol).map_or(0, |position| position.amount)
+    }
+
+    pub(crate) fn total_invested(&self, symbol: &str) -> f64 {
+        self.positions.get(symbol).map_or(0.0, |position| position.total_invested)
+    }
+
+    pub(crate) fn set_total_invested(&mut self, symbol: &str, total_invested: f64) {
+        let position = self.position(symbol);
+
+        position.total_invested = total_invested;
+        position.average_price = if position.amount > 0 { position.total_invested / position.amount as f64 } else { 0.0 };
+    }
+
+    pub(crate) fn close(&mut self, symbol: &str) {
+        self.positions.remove(symbol);
+    }
+
+    // Splits and reverse splits change the amount while keeping the total invested
+    pub(crate) fn convert(&mut self, symbol: &str, factor: f64) {
+        let position = self.position(symbol);
+
+        position.amount = (position.amount as f64 * factor).floor() as i64;
+        position.average_price = if position.amount > 0 { position.total_invested / position.amount as f64 } else { 0.0 };
+    }
+
+    pub(crate) fn into_positions(self) -> Vec<Position> {
+        self.positions
+            .into_iter()
+            .map(|(_, position)| position)
+            .filter(|position| position.amount > 0)
+            .collect()
+    }
+
+    fn position(&mut self, symbol: &str) -> &mut Position {
+        self.positions
+            .entry(symbol.to_string())
+            .or_insert_with(|| Position {
+                symbol: symbol.to_string(),
+                amount: 0,
+                average_price: 0.0,
+                total_invested: 0.0,
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn purchases_average_the_price() {
+        let mut book = PositionBook::default();
+
+        book.buy("PETR4", 100, 2000.0);
+        book.buy("PETR4", 100, 3000.0);
+
+        assert_eq!(book.amount("PETR4"), 200);
+        assert_eq!(book.total_invested("PETR4"), 5000.0);
+        assert_eq!(book.sell("PETR4", 50), 25.0);
+        assert_eq!(book.total_invested("PETR4"), 3750.0);
+    }
+
+    #[test]
+    fn overselling_stops_at_zero() {
+        let mut book = PositionBook::default();
+
+        book.buy("PETR4", 100, 2000.0);
+        book.sell("PETR4", 150);
+
+        assert_eq!(book.amount("PETR4"), 0);
+        assert_eq!(book.total_invested("PETR4"), 0.0);
+        assert!(book.into_positions().is_empty());
+    }
+
+    #[test]
+    fn buying_into_empty_position_drops_stale_cost() {
+        let mut book = PositionBook::default();
+
+        // A reverse split leaving less than one share keeps the cost on an empty position
+        book.buy("PETR4", 5, 100.0);
+        book.convert("PETR4", 0.1);
+        assert_eq!(book.amount("PETR4"), 0);
+
+        book.buy("PETR4", 10, 300.0);
+
+        assert_eq!(book.total_invested("PETR4"), 300.0);
+        assert_eq!(book.sell("PETR4", 10), 30.0);
+    }
+}
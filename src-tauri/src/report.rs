@@ -0,0 +1,266 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use chrono::{Datelike, Duration, NaiveDate};
+use tauri::{InvokeError, State};
+
+use crate::asset;
+use crate::asset::types::{Asset, AssetClass};
+use crate::corporate_event;
+use crate::corporate_event::types::CorporateEventType;
+use crate::database::Database;
+use crate::error::Error;
+use crate::income;
+use crate::income::types::IncomeType;
+use crate::position;
+use crate::position::types::Position;
+use crate::report::types::{assets_and_rights_code, AssetAndRight, IncomeCategory, IncomeReportItem};
+use crate::tax;
+
+pub(crate) mod types;
+
+const CANT_WRITE_REPORT_FILE: &str = "CANT_WRITE_REPORT_FILE";
+
+// Holdings on December 31 of the year and of the year before, valued at their cost
+#[tauri::command(async)]
+pub(crate) fn get_assets_and_rights_report(database: State<'_, Database>, year: i32) -> Result<Vec<AssetAndRight>, InvokeError> {
+    let connection = database.connection()?;
+
+    Ok(assets_and_rights_report(&connection, year)?)
+}
+
+#[tauri::command(async)]
+pub(crate) fn export_assets_and_rights_report(database: State<'_, Database>, year: i32, file_path: String) -> Result<(), InvokeError> {
+    let connection = database.connection()?;
+
+    let report = assets_and_rights_report(&connection, year)?;
+
+    let mut rows = vec![vec![
+        String::from("Grupo"),
+        String::from("Código"),
+        String::from("CNPJ"),
+        String::from("Discriminação"),
+        format!("Situação em 31/12/{}", year - 1),
+        format!("Situação em 31/12/{}", year),
+    ]];
+
+    for item in report {
+        rows.push(vec![
+            item.group,
+            item.code,
+            item.cnpj.unwrap_or_default(),
+            item.description,
+            format_decimal(item.previous_value),
+            format_decimal(item.value),
+        ]);
+    }
+
+    Ok(write_csv(&file_path, &rows)?)
+}
+
+fn assets_and_rights_report(connection: &rusqlite::Connection, year: i32) -> Result<Vec<AssetAndRight>, Error> {
+    let year_end = NaiveDate::from_ymd(year, 12, 31);
+    let previous_year_end = NaiveDate::from_ymd(year - 1, 12, 31);
+
+    let positions = position::select_positions(connection, None, Some(year_end))?;
+    let previous_positions = position::select_positions(connection, None, Some(previous_year_end))?;
+
+    let assets: BTreeMap<String, Asset> = asset::db::select_assets(connection)?
+        .into_iter()
+        .map(|asset| (asset.symbol.clone(), asset))
+        .collect();
+
+    let mut broker_names: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+    for order in position::db::select_position_orders(connection)? {
+        if order.trading_date.naive_local().date() <= year_end {
+            broker_names.entry(order.symbol).or_insert_with(BTreeSet::new).insert(order.broker_name);
+        }
+    }
+
+    let symbols: BTreeSet<&String> = positions.iter()
+        .chain(previous_positions.iter())
+        .map(|position| &position.symbol)
+        .collect();
+
+    let find_position = |positions: &[Position], symbol: &str| {
+        positions.iter()
+            .find(|position| position.symbol == symbol)
+            .map_or((0, 0.0, 0.0), |position| (position.amount, position.total_invested, position.average_price))
+    };
+
+    let mut report = Vec::new();
+    for symbol in symbols {
+        let asset = assets.get(symbol);
+        let asset_class = asset.map_or_else(|| AssetClass::guess(symbol), |asset| asset.asset_class);
+        let (group, code) = assets_and_rights_code(&asset_class);
+
+        let (previous_amount, previous_value, _) = find_position(&previous_positions, symbol);
+        let (amount, value, average_price) = find_position(&positions, symbol);
+
+        report.push(AssetAndRight {
+            group: group.to_string(),
+            code: code.to_string(),
+            symbol: symbol.to_string(),
+            asset_class,
+            cnpj: asset.and_then(|asset| asset.cnpj.clone()),
+            description: description(symbol, &asset_class, asset, amount, average_price, broker_names.get(symbol)),
+            previous_amount,
+            previous_value,
+            amount,
+            value,
+        });
+    }
+
+    Ok(report)
+}
+
+// Totals per payer for the exempt and exclusive taxation sections. JCP is declared net of
+// the withholding and bonus shares at the cost assigned by the company.
+#[tauri::command(async)]
+pub(crate) fn get_income_report(database: State<'_, Database>, year: i32) -> Result<Vec<IncomeReportItem>, InvokeError> {
+    let connection = database.connection()?;
+
+    let assets: BTreeMap<String, Asset> = asset::db::select_assets(&connection)?
+        .into_iter()
+        .map(|asset| (asset.symbol.clone(), asset))
+        .collect();
+
+    let mut amounts: BTreeMap<(IncomeCategory, String), f64> = BTreeMap::new();
+
+    for income in income::db::select_income_by_year(&connection, &year)? {
+        let (category, amount) = match income.income_type {
+            IncomeType::Dividend => (IncomeCategory::Dividends, income.net_amount),
+            IncomeType::RealEstateFundIncome => (IncomeCategory::RealEstateFundIncome, income.net_amount),
+            IncomeType::InterestOnEquity => (IncomeCategory::InterestOnEquity, income.net_amount),
+            IncomeType::Other => continue,
+        };
+
+        *amounts.entry((category, income.symbol)).or_insert(0.0) += amount;
+    }
+
+    for corporate_event in corporate_event::db::select_corporate_events(&connection, &None)? {
+        if corporate_event.event_type != CorporateEventType::Bonus || corporate_event.ex_date.year() != year {
+            continue;
+        }
+
+        let bonus_amount = match corporate_event.amount {
+            Some(amount) => amount,
+            None => {
+                let held_amount = position::select_positions(&connection, None, Some(corporate_event.ex_date - Duration::days(1)))?
+                    .iter()
+                    .find(|position| position.symbol == corporate_event.symbol)
+                    .map_or(0, |position| position.amount);
+
+                (held_amount as f64 * corporate_event.ratio_to / corporate_event.ratio_from).floor() as i64
+            }
+        };
+
+        *amounts.entry((IncomeCategory::BonusShares, corporate_event.symbol)).or_insert(0.0) +=
+            bonus_amount as f64 * corporate_event.unit_cost.unwrap_or(0.0);
+    }
+
+    let exempt_stock_gains: f64 = tax::select_monthly_taxes_by_year(&connection, year)?
+        .iter()
+        .filter(|monthly_tax| monthly_tax.exempt)
+        .map(|monthly_tax| monthly_tax.stock_result)
+        .sum();
+
+    // Symbols of the same company are declared together under its CNPJ
+    let mut items: BTreeMap<(IncomeCategory, String), IncomeReportItem> = BTreeMap::new();
+    for ((category, symbol), amount) in amounts {
+        let asset = assets.get(&symbol);
+        let cnpj = asset.and_then(|asset| asset.cnpj.clone());
+        let payer_name = asset.and_then(|asset| asset.issuer_name.clone());
+
+        items
+            .entry((category, cnpj.clone().unwrap_or(symbol.clone())))
+            .or_insert_with(|| IncomeReportItem {
+                section: category.section(),
+                category,
+                code: category.code().to_string(),
+                cnpj,
+                payer_name: payer_name.or(Some(symbol)),
+                amount: 0.0,
+            })
+            .amount += amount;
+    }
+
+    let mut report: Vec<IncomeReportItem> = items.into_iter()
+        .map(|(_, item)| item)
+        .collect();
+
+    if exempt_stock_gains > 0.0 {
+        report.push(IncomeReportItem {
+            section: IncomeCategory::ExemptStockGains.section(),
+            category: IncomeCategory::ExemptStockGains,
+            code: IncomeCategory::ExemptStockGains.code().to_string(),
+            cnpj: None,
+            payer_name: None,
+            amount: exempt_stock_gains,
+        });
+    }
+
+    report.sort_by_key(|item| (item.section, item.category));
+
+    Ok(report)
+}
+
+fn description(symbol: &str, asset_class: &AssetClass, asset: Option<&Asset>, amount: i64, average_price: f64, broker_names: Option<&BTreeSet<String>>) -> String {
+    if amount == 0 {
+        return format!("{} - posição encerrada", symbol);
+    }
+
+    let unit = match asset_class {
+        AssetClass::Stock => "ações",
+        AssetClass::Unit => "units",
+        AssetClass::RealEstateFund | AssetClass::Etf => "cotas",
+        AssetClass::Bdr => "BDRs",
+        AssetClass::Option => "opções",
+        AssetClass::SubscriptionRight => "direitos de subscrição",
+        AssetClass::SubscriptionReceipt => "recibos de subscrição",
+        AssetClass::Other => "unidades",
+    };
+
+    let mut description = format!("{} {} {}", amount, unit, symbol);
+
+    if let Some(issuer_name) = asset.and_then(|asset| asset.issuer_name.as_ref()) {
+        description.push_str(&format!(" de {}", issuer_name));
+    }
+
+    description.push_str(&format!(", preço médio de R$ {}", format_decimal(average_price)));
+
+    if let Some(broker_names) = broker_names {
+        let broker_names: Vec<&str> = broker_names.iter().map(|name| name.as_str()).collect();
+        description.push_str(&format!(", custódia em {}", broker_names.join(", ")));
+    }
+
+    description
+}
+
+fn format_decimal(value: f64) -> String {
+    format!("{:.2}", value).replace('.', ",")
+}
+
+// Semicolons and decimal commas, as expected by spreadsheets in Brazilian locale
+fn write_csv(file_path: &str, rows: &[Vec<String>]) -> Result<(), Error> {
+    let mut writer = csv::WriterBuilder::new()
+        .delimiter(b';')
+        .from_path(file_path)
+        .map_err(|error| {
+            println!("Erro ao gravar relatório: {}", error);
+            Error::Io(CANT_WRITE_REPORT_FILE, error.into())
+        })?;
+
+    for row in rows {
+        writer.write_record(row)
+            .map_err(|error| {
+                println!("Erro ao gravar relatório: {}", error);
+                Error::Io(CANT_WRITE_REPORT_FILE, error.into())
+            })?;
+    }
+
+    writer.flush()
+        .map_err(|error| {
+            println!("Erro ao gravar relatório: {}", error);
+            Error::Io(CANT_WRITE_REPORT_FILE, error)
+        })
+}
@@ -0,0 +1,74 @@
+use serde::{Deserialize, Serialize};
+
+use crate::asset::types::AssetClass;
+
+// Group and code of the "Bens e Direitos" section, as laid out since the 2022 declaration
+pub(crate) fn assets_and_rights_code(asset_class: &AssetClass) -> (&'static str, &'static str) {
+    match asset_class {
+        AssetClass::Stock | AssetClass::Unit => ("03", "01"),
+        AssetClass::RealEstateFund => ("07", "03"),
+        AssetClass::Etf => ("07", "08"),
+        AssetClass::Bdr | AssetClass::Option | AssetClass::SubscriptionRight | AssetClass::SubscriptionReceipt | AssetClass::Other => ("04", "04"),
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub(crate) struct AssetAndRight {
+    pub(crate) group: String,
+    pub(crate) code: String,
+    pub(crate) symbol: String,
+    pub(crate) asset_class: AssetClass,
+    pub(crate) cnpj: Option<String>,
+    pub(crate) description: String,
+    pub(crate) previous_amount: i64,
+    pub(crate) previous_value: f64,
+    pub(crate) amount: i64,
+    pub(crate) value: f64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub(crate) enum IncomeSection {
+    Exempt,
+    ExclusiveTaxation,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub(crate) enum IncomeCategory {
+    Dividends,
+    RealEstateFundIncome,
+    ExemptStockGains,
+    BonusShares,
+    InterestOnEquity,
+}
+
+impl IncomeCategory {
+    pub(crate) fn section(&self) -> IncomeSection {
+        match self {
+            IncomeCategory::InterestOnEquity => IncomeSection::ExclusiveTaxation,
+            _ => IncomeSection::Exempt,
+        }
+    }
+
+    // Income type codes of the 2022 declaration layout
+    pub(crate) fn code(&self) -> &'static str {
+        match self {
+            IncomeCategory::Dividends => "09",
+            IncomeCategory::RealEstateFundIncome => "26",
+            IncomeCategory::ExemptStockGains => "20",
+            IncomeCategory::BonusShares => "18",
+            IncomeCategory::InterestOnEquity => "10",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub(crate) struct IncomeReportItem {
+    pub(crate) section: IncomeSection,
+    pub(crate) category: IncomeCategory,
+    pub(crate) code: String,
+    pub(crate) cnpj: Option<String>,
+    pub(crate) payer_name: Option<String>,
+    pub(crate) amount: f64,
+}
@@ -0,0 +1,155 @@
+use tauri::{InvokeError, State};
+
+use crate::asset::types::AssetClass;
+use crate::darf;
+use crate::database::Database;
+use crate::error::Error;
+use crate::tax::types::{AccumulatedLoss, MonthlyTax};
+
+pub(crate) mod db;
+pub(crate) mod types;
+
+#[tauri::command(async)]
+pub(crate) fn get_monthly_tax(database: State<'_, Database>, year: i32) -> Result<Vec<MonthlyTax>, InvokeError> {
+    let connection = database.connection()?;
+
+    Ok(select_monthly_taxes_by_year(&connection, year)?)
+}
+
+#[tauri::command(async)]
+pub(crate) fn get_accumulated_losses(database: State<'_, Database>, year: i32) -> Result<Vec<AccumulatedLoss>, InvokeError> {
+    let connection = database.connection()?;
+
+    Ok(db::select_accumulated_losses_by_year(&connection, &year)?)
+}
+
+pub(crate) fn select_monthly_taxes_by_year(connection: &rusqlite::Connection, year: i32) -> Result<Vec<MonthlyTax>, Error> {
+    let (monthly_taxes, _) = calc_monthly_taxes(connection, Some(format!("{:04}-12", year)))?;
+
+    let year_prefix = format!("{:04}-", year);
+
+    Ok(
+        monthly_taxes
+            .into_iter()
+            .filter(|monthly_tax| monthly_tax.month.starts_with(&year_prefix))
+            .collect()
+    )
+}
+
+pub(crate) fn update_tax_records(transaction: &rusqlite::Transaction) -> Result<(), Error> {
+    let (monthly_taxes, accumulated_losses) = calc_monthly_taxes(transaction, None)?;
+
+    db::delete_accumulated_losses(transaction)?;
+    db::insert_accumulated_losses(transaction, &accumulated_losses)?;
+
+    darf::update_darfs(transaction, &monthly_taxes)
+}
+
+// Losses are carried forward month by month, so every month is computed from the first month
+// with results up to `last_month` (or the last month with results).
+fn calc_monthly_taxes(connection: &rusqlite::Connection, last_month: Option<String>) -> Result<(Vec<MonthlyTax>, Vec<AccumulatedLoss>), Error> {
+    let swing_trade_results = db::select_swing_trade_results(connection)?;
+    let day_trade_results = db::select_day_trade_results(connection)?;
+    let withheld_income_taxes = db::select_withheld_income_taxes(connection)?;
+
+    let result_months = swing_trade_results.iter()
+        .chain(day_trade_results.iter())
+        .map(|result| result.month.clone());
+
+    let first_month = result_months.clone().chain(last_month.clone()).min();
+    let last_month = result_months.chain(last_month).max();
+
+    let (mut month, last_month) = match (first_month, last_month) {
+        (Some(first_month), Some(last_month)) => (first_month, last_month),
+        _ => return Ok((Vec::new(), Vec::new())),
+    };
+
+    let mut monthly_taxes = Vec::new();
+    let mut accumulated_losses = Vec::new();
+    let mut previous_losses = Vec::new();
+
+    while month <= last_month {
+        let mut monthly_tax = MonthlyTax {
+            month: month.clone(),
+            ..MonthlyTax::default()
+        };
+
+        for swing_trade_result in swing_trade_results.iter().filter(|result| result.month == month) {
+            match swing_trade_result.asset_class {
+                AssetClass::RealEstateFund => {
+                    monthly_tax.real_estate_fund_sales += swing_trade_result.sales;
+                    monthly_tax.real_estate_fund_result += swing_trade_result.result;
+                }
+                AssetClass::Stock | AssetClass::Unit => {
+                    monthly_tax.stock_sales += swing_trade_result.sales;
+                    monthly_tax.stock_result += swing_trade_result.result;
+                    monthly_tax.swing_trade_result += swing_trade_result.result;
+                }
+                AssetClass::Etf => {
+                    monthly_tax.etf_sales += swing_trade_result.sales;
+                    monthly_tax.swing_trade_result += swing_trade_result.result;
+                }
+                AssetClass::Bdr => {
+                    monthly_tax.bdr_sales += swing_trade_result.sales;
+                    monthly_tax.swing_trade_result += swing_trade_result.result;
+                }
+                _ => {
+                    monthly_tax.other_sales += swing_trade_result.sales;
+                    monthly_tax.swing_trade_result += swing_trade_result.result;
+                }
+            }
+        }
+
+        for day_trade_result in day_trade_results.iter().filter(|result| result.month == month) {
+            match day_trade_result.asset_class {
+                AssetClass::RealEstateFund => {
+                    monthly_tax.real_estate_fund_sales += day_trade_result.sales;
+                    monthly_tax.real_estate_fund_result += day_trade_result.result;
+                }
+                _ => monthly_tax.day_trade_result += day_trade_result.result,
+            }
+        }
+
+        monthly_tax.withheld_income_tax = withheld_income_taxes.iter()
+            .filter(|(income_tax_month, _)| *income_tax_month == month)
+            .map(|(_, income_tax)| income_tax)
+            .sum();
+
+        let losses = monthly_tax.calc(&previous_losses);
+        monthly_taxes.push(monthly_tax);
+
+        accumulated_losses.append(&mut previous_losses);
+        previous_losses = losses;
+
+        month = next_month(&month);
+    }
+
+    accumulated_losses.append(&mut previous_losses);
+
+    Ok((monthly_taxes, accumulated_losses))
+}
+
+pub(crate) fn next_month(month: &str) -> String {
+    let (year, month) = split_month(month);
+
+    if month == 12 { format!("{:04}-01", year + 1) } else { format!("{:04}-{:02}", year, month + 1) }
+}
+
+// Strict "YYYY-MM", for months that come from the frontend
+pub(crate) fn parse_month(month: &str) -> Option<(i32, u32)> {
+    if month.len() != 7 || month.get(4..5) != Some("-") {
+        return None;
+    }
+
+    let year = month.get(..4)?.parse().ok()?;
+    let month = month.get(5..)?.parse().ok()?;
+
+    if (1..=12).contains(&month) { Some((year, month)) } else { None }
+}
+
+pub(crate) fn split_month(month: &str) -> (i32, u32) {
+    let year = month.get(..4).and_then(|year| year.parse().ok()).unwrap_or(0);
+    let month = month.get(5..7).and_then(|month| month.parse().ok()).unwrap_or(1);
+
+    (year, month)
+}
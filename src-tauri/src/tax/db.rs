@@ -0,0 +1,222 @@
+use rusqlite::named_params;
+
+use crate::asset::types::AssetClass;
+use crate::commons::{QueryMapper, ToVec};
+use crate::error::Error;
+use crate::tax::types::{AccumulatedLoss, MonthlyResult};
+
+const ACCUMULATED_LOSS_MAPPER: QueryMapper<AccumulatedLoss> = |row| {
+    Ok(AccumulatedLoss {
+        month: row.get(0)?,
+        category: row.get(1)?,
+        available: row.get(2)?,
+        incurred: row.get(3)?,
+        consumed: row.get(4)?,
+        remaining: row.get(5)?,
+    })
+};
+
+// Symbols traded before the asset registry existed fall back to the guessed class
+const MONTHLY_RESULT_MAPPER: QueryMapper<MonthlyResult> = |row| {
+    let symbol: String = row.get(1)?;
+    let asset_class: Option<AssetClass> = row.get(2)?;
+
+    Ok(MonthlyResult {
+        month: row.get(0)?,
+        asset_class: asset_class.unwrap_or_else(|| AssetClass::guess(&symbol)),
+        sales: row.get(3)?,
+        result: row.get(4)?,
+    })
+};
+
+pub(crate) fn select_swing_trade_results(connection: &rusqlite::Connection) -> Result<Vec<MonthlyResult>, Error> {
+    let mut statement = connection.prepare_cached(
+    "SELECT
+            r.mes,
+            r.papel,
+            a.classe,
+            SUM(r.valor_venda),
+            SUM(r.resultado)
+        FROM (
+            SELECT
+                STRFTIME('%Y-%m', ro.data_pregao) AS mes,
+                ro.papel AS papel,
+                nco.valor_ordem * ro.quantidade / nco.quantidade AS valor_venda,
+                ro.resultado AS resultado
+            FROM resultado_operacao ro
+                INNER JOIN nota_corretagem_ordem nco ON
+                    ro.nota_corretagem_ordem_id = nco.id
+            UNION ALL
+            SELECT
+                STRFTIME('%Y-%m', rec.data_ex),
+                rec.papel,
+                rec.valor_recebido,
+                rec.resultado
+            FROM resultado_evento_corporativo rec
+        ) r
+            LEFT JOIN ativo a ON
+                a.papel = r.papel
+        GROUP BY r.mes, r.papel
+        ORDER BY r.mes ASC, r.papel ASC"
+    )?;
+
+    let result_rows = statement.query_map(
+        [],
+        MONTHLY_RESULT_MAPPER
+    )?;
+
+    Ok(result_rows.to_vec())
+}
+
+pub(crate) fn select_day_trade_results(connection: &rusqlite::Connection) -> Result<Vec<MonthlyResult>, Error> {
+    let mut statement = connection.prepare_cached(
+    "SELECT
+            STRFTIME('%Y-%m', rdt.data_pregao) AS mes,
+            rdt.papel,
+            a.classe,
+            SUM(rdt.preco_medio_venda * rdt.quantidade),
+            SUM(rdt.resultado)
+        FROM resultado_day_trade rdt
+            LEFT JOIN ativo a ON
+                a.papel = rdt.papel
+        GROUP BY mes, rdt.papel
+        ORDER BY mes ASC, rdt.papel ASC"
+    )?;
+
+    let result_rows = statement.query_map(
+        [],
+        MONTHLY_RESULT_MAPPER
+    )?;
+
+    Ok(result_rows.to_vec())
+}
+
+pub(crate) fn select_withheld_income_taxes(connection: &rusqlite::Connection) -> Result<Vec<(String, f64)>, Error> {
+    let mut statement = connection.prepare_cached(
+    "SELECT
+            STRFTIME('%Y-%m', nc.data_pregao) AS mes,
+            SUM(nco.irrf)
+        FROM nota_corretagem_ordem nco
+            INNER JOIN nota_corretagem nc ON
+                nco.nota_corretagem_id = nc.id
+        WHERE nco.tipo = 'V'
+        GROUP BY mes"
+    )?;
+
+    let income_tax_rows = statement.query_map(
+        [],
+        |row| Ok((row.get(0)?, row.get(1)?))
+    )?;
+
+    income_tax_rows
+        .collect::<rusqlite::Result<Vec<(String, f64)>>>()
+        .map_err(Error::from)
+}
+
+pub(crate) fn select_accumulated_losses_by_year(connection: &rusqlite::Connection, year: &i32) -> Result<Vec<AccumulatedLoss>, Error> {
+    let mut statement = connection.prepare_cached(
+    "SELECT
+            mes,
+            categoria,
+            disponivel,
+            incorrido,
+            consumido,
+            remanescente
+        FROM prejuizo_acumulado
+        WHERE SUBSTR(mes, 1, 4) = :year
+        ORDER BY mes ASC, id ASC"
+    )?;
+
+    let loss_rows = statement.query_map(
+        named_params! { ":year": year.to_string() },
+        ACCUMULATED_LOSS_MAPPER
+    )?;
+
+    Ok(loss_rows.to_vec())
+}
+
+pub(crate) fn delete_accumulated_losses(transaction: &rusqlite::Transaction) -> Result<(), Error> {
+    transaction.execute("DELETE FROM prejuizo_acumulado", [])?;
+
+    Ok(())
+}
+
+pub(crate) fn insert_accumulated_losses(transaction: &rusqlite::Transaction, accumulated_losses: &Vec<AccumulatedLoss>) -> Result<(), Error> {
+    let mut statement = transaction.prepare_cached(
+        "INSERT INTO prejuizo_acumulado (
+            mes,
+            categoria,
+            disponivel,
+            incorrido,
+            consumido,
+            remanescente
+        ) VALUES (
+            :month,
+            :category,
+            :available,
+            :incurred,
+            :consumed,
+            :remaining
+        )"
+    )?;
+
+    for accumulated_loss in accumulated_losses {
+        statement.insert(named_params! {
+            ":month": accumulated_loss.month,
+            ":category": accumulated_loss.category,
+            ":available": accumulated_loss.available,
+            ":incurred": accumulated_loss.incurred,
+            ":consumed": accumulated_loss.consumed,
+            ":remaining": accumulated_loss.remaining
+        })?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use super::*;
+    use crate::corporate_event;
+    use crate::corporate_event::types::{CorporateEvent, CorporateEventType};
+    use crate::database;
+    use crate::position;
+    use crate::position::types::CorporateEventResult;
+
+    #[test]
+    fn corporate_event_gains_are_swing_trade_results() {
+        let mut connection = database::open_in_memory();
+        let transaction = connection.transaction().unwrap();
+
+        let corporate_event_id = corporate_event::db::insert_corporate_event(&transaction, &CorporateEvent {
+            id: 0,
+            symbol: "ABCD3".to_string(),
+            event_type: CorporateEventType::Merger,
+            ex_date: NaiveDate::from_ymd(2021, 3, 1),
+            ratio_from: 1.0,
+            ratio_to: 1.0,
+            unit_cost: None,
+            cash_amount: Some(15.0),
+            amount: None,
+            targets: Vec::new(),
+        }).unwrap();
+
+        position::db::insert_corporate_event_results(&transaction, &vec![CorporateEventResult {
+            id: 0,
+            corporate_event_id,
+            ex_date: NaiveDate::from_ymd(2021, 3, 1),
+            symbol: "ABCD3".to_string(),
+            amount: 100,
+            cost_basis: 1000.0,
+            proceeds: 1500.0,
+            result: 500.0,
+        }]).unwrap();
+
+        let results = select_swing_trade_results(&transaction).unwrap();
+
+        assert_eq!(results.len(), 1);
+        assert_eq!((results[0].month.as_str(), results[0].asset_class, results[0].sales, results[0].result), ("2021-03", AssetClass::Stock, 1500.0, 500.0));
+    }
+}
@@ -0,0 +1,264 @@
+use rusqlite::types::{FromSql, FromSqlError, FromSqlResult, ToSql, ToSqlOutput, ValueRef};
+use serde::{Deserialize, Serialize};
+
+use crate::asset::types::AssetClass;
+
+const STOCK_SALES_EXEMPTION_LIMIT: f64 = 20000.0;
+const SWING_TRADE_RATE: f64 = 0.15;
+const DAY_TRADE_RATE: f64 = 0.20;
+const REAL_ESTATE_FUND_RATE: f64 = 0.20;
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub(crate) struct MonthlyTax {
+    pub(crate) month: String,
+    pub(crate) stock_sales: f64,
+    pub(crate) etf_sales: f64,
+    pub(crate) bdr_sales: f64,
+    pub(crate) other_sales: f64,
+    pub(crate) exempt: bool,
+    pub(crate) stock_result: f64,
+    pub(crate) swing_trade_result: f64,
+    pub(crate) swing_trade_loss_offset: f64,
+    pub(crate) swing_trade_tax: f64,
+    pub(crate) day_trade_result: f64,
+    pub(crate) day_trade_loss_offset: f64,
+    pub(crate) day_trade_tax: f64,
+    pub(crate) real_estate_fund_sales: f64,
+    pub(crate) real_estate_fund_result: f64,
+    pub(crate) real_estate_fund_loss_offset: f64,
+    pub(crate) real_estate_fund_tax: f64,
+    pub(crate) withheld_income_tax: f64,
+    pub(crate) tax_due: f64,
+}
+
+impl MonthlyTax {
+    pub(crate) fn calc(&mut self, previous_losses: &[AccumulatedLoss]) -> Vec<AccumulatedLoss> {
+        // Only stock gains are exempt; ETFs, BDRs and other assets are always taxable but share the pool
+        self.exempt = self.stock_sales <= STOCK_SALES_EXEMPTION_LIMIT && self.stock_result > 0.0;
+        let taxable_result = if self.exempt { self.swing_trade_result - self.stock_result } else { self.swing_trade_result };
+
+        let swing_trade_loss = AccumulatedLoss::new(&self.month, LossCategory::SwingTrade, previous_losses, taxable_result);
+        self.swing_trade_loss_offset = swing_trade_loss.consumed;
+        self.swing_trade_tax = (taxable_result - self.swing_trade_loss_offset).max(0.0) * SWING_TRADE_RATE;
+
+        let day_trade_loss = AccumulatedLoss::new(&self.month, LossCategory::DayTrade, previous_losses, self.day_trade_result);
+        self.day_trade_loss_offset = day_trade_loss.consumed;
+        self.day_trade_tax = (self.day_trade_result - self.day_trade_loss_offset).max(0.0) * DAY_TRADE_RATE;
+
+        // FII gains have no exemption and both swing and day trades share a single loss pool
+        let real_estate_fund_loss = AccumulatedLoss::new(&self.month, LossCategory::RealEstateFund, previous_losses, self.real_estate_fund_result);
+        self.real_estate_fund_loss_offset = real_estate_fund_loss.consumed;
+        self.real_estate_fund_tax = (self.real_estate_fund_result - self.real_estate_fund_loss_offset).max(0.0) * REAL_ESTATE_FUND_RATE;
+
+        self.tax_due = (self.swing_trade_tax + self.day_trade_tax + self.real_estate_fund_tax - self.withheld_income_tax).max(0.0);
+
+        vec![swing_trade_loss, day_trade_loss, real_estate_fund_loss]
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub(crate) enum LossCategory {
+    SwingTrade,
+    DayTrade,
+    RealEstateFund,
+}
+
+impl ToSql for LossCategory {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(ToSqlOutput::from(match self {
+            LossCategory::SwingTrade => "SWING_TRADE",
+            LossCategory::DayTrade => "DAY_TRADE",
+            LossCategory::RealEstateFund => "REAL_ESTATE_FUND",
+        }))
+    }
+}
+
+impl FromSql for LossCategory {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        match value.as_str()? {
+            "SWING_TRADE" => Ok(LossCategory::SwingTrade),
+            "DAY_TRADE" => Ok(LossCategory::DayTrade),
+            "REAL_ESTATE_FUND" => Ok(LossCategory::RealEstateFund),
+            _ => Err(FromSqlError::InvalidType),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub(crate) struct AccumulatedLoss {
+    pub(crate) month: String,
+    pub(crate) category: LossCategory,
+    pub(crate) available: f64,
+    pub(crate) incurred: f64,
+    pub(crate) consumed: f64,
+    pub(crate) remaining: f64,
+}
+
+impl AccumulatedLoss {
+    // Losses never expire; they are only consumed by taxable gains of the same category
+    fn new(month: &str, category: LossCategory, previous_losses: &[AccumulatedLoss], taxable_result: f64) -> Self {
+        let available = previous_losses.iter()
+            .find(|loss| loss.category == category)
+            .map_or(0.0, |loss| loss.remaining);
+        let incurred = (-taxable_result).max(0.0);
+        let consumed = available.min(taxable_result.max(0.0));
+
+        AccumulatedLoss {
+            month: month.to_string(),
+            category,
+            available,
+            incurred,
+            consumed,
+            remaining: available - consumed + incurred,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct MonthlyResult {
+    pub(crate) month: String,
+    pub(crate) asset_class: AssetClass,
+    pub(crate) sales: f64,
+    pub(crate) result: f64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn remaining(losses: &[AccumulatedLoss], category: LossCategory) -> f64 {
+        losses.iter().find(|loss| loss.category == category).map_or(0.0, |loss| loss.remaining)
+    }
+
+    #[test]
+    fn stock_gains_under_limit_are_exempt() {
+        let mut monthly_tax = MonthlyTax {
+            month: "2021-03".to_string(),
+            stock_sales: 19000.0,
+            stock_result: 3000.0,
+            swing_trade_result: 3000.0,
+            ..MonthlyTax::default()
+        };
+
+        monthly_tax.calc(&[]);
+
+        assert!(monthly_tax.exempt);
+        assert_eq!(monthly_tax.swing_trade_tax, 0.0);
+        assert_eq!(monthly_tax.tax_due, 0.0);
+    }
+
+    #[test]
+    fn etf_gains_are_taxed_even_when_stocks_are_exempt() {
+        let mut monthly_tax = MonthlyTax {
+            month: "2021-03".to_string(),
+            stock_sales: 10000.0,
+            etf_sales: 5000.0,
+            stock_result: 1000.0,
+            swing_trade_result: 2000.0,
+            ..MonthlyTax::default()
+        };
+
+        monthly_tax.calc(&[]);
+
+        assert!(monthly_tax.exempt);
+        assert_eq!(monthly_tax.swing_trade_tax, 150.0);
+    }
+
+    #[test]
+    fn stock_gains_over_limit_are_taxed() {
+        let mut monthly_tax = MonthlyTax {
+            month: "2021-03".to_string(),
+            stock_sales: 25000.0,
+            stock_result: 3000.0,
+            swing_trade_result: 3000.0,
+            ..MonthlyTax::default()
+        };
+
+        monthly_tax.calc(&[]);
+
+        assert!(!monthly_tax.exempt);
+        assert_eq!(monthly_tax.swing_trade_tax, 450.0);
+        assert_eq!(monthly_tax.tax_due, 450.0);
+    }
+
+    #[test]
+    fn losses_are_carried_forward_and_consumed() {
+        let mut loss_month = MonthlyTax {
+            month: "2021-03".to_string(),
+            stock_sales: 30000.0,
+            stock_result: -1000.0,
+            swing_trade_result: -1000.0,
+            day_trade_result: -200.0,
+            ..MonthlyTax::default()
+        };
+        let losses = loss_month.calc(&[]);
+
+        assert_eq!(remaining(&losses, LossCategory::SwingTrade), 1000.0);
+        assert_eq!(remaining(&losses, LossCategory::DayTrade), 200.0);
+
+        // An exempt month does not consume the losses
+        let mut exempt_month = MonthlyTax {
+            month: "2021-04".to_string(),
+            stock_sales: 5000.0,
+            stock_result: 500.0,
+            swing_trade_result: 500.0,
+            ..MonthlyTax::default()
+        };
+        let losses = exempt_month.calc(&losses);
+
+        assert_eq!(exempt_month.swing_trade_loss_offset, 0.0);
+        assert_eq!(remaining(&losses, LossCategory::SwingTrade), 1000.0);
+
+        let mut gain_month = MonthlyTax {
+            month: "2021-05".to_string(),
+            stock_sales: 30000.0,
+            stock_result: 3000.0,
+            swing_trade_result: 3000.0,
+            day_trade_result: 100.0,
+            ..MonthlyTax::default()
+        };
+        let losses = gain_month.calc(&losses);
+
+        assert_eq!(gain_month.swing_trade_loss_offset, 1000.0);
+        assert_eq!(gain_month.swing_trade_tax, 300.0);
+        assert_eq!(gain_month.day_trade_loss_offset, 100.0);
+        assert_eq!(gain_month.day_trade_tax, 0.0);
+        assert_eq!(remaining(&losses, LossCategory::SwingTrade), 0.0);
+        assert_eq!(remaining(&losses, LossCategory::DayTrade), 100.0);
+    }
+
+    #[test]
+    fn real_estate_fund_losses_are_kept_apart() {
+        let mut monthly_tax = MonthlyTax {
+            month: "2021-03".to_string(),
+            stock_sales: 30000.0,
+            stock_result: 1000.0,
+            swing_trade_result: 1000.0,
+            real_estate_fund_sales: 5000.0,
+            real_estate_fund_result: -400.0,
+            ..MonthlyTax::default()
+        };
+
+        let losses = monthly_tax.calc(&[]);
+
+        assert_eq!(monthly_tax.swing_trade_tax, 150.0);
+        assert_eq!(monthly_tax.real_estate_fund_tax, 0.0);
+        assert_eq!(remaining(&losses, LossCategory::RealEstateFund), 400.0);
+    }
+
+    #[test]
+    fn withheld_income_tax_is_deducted() {
+        let mut monthly_tax = MonthlyTax {
+            month: "2021-03".to_string(),
+            day_trade_result: 1000.0,
+            withheld_income_tax: 10.0,
+            ..MonthlyTax::default()
+        };
+
+        monthly_tax.calc(&[]);
+
+        assert_eq!(monthly_tax.day_trade_tax, 200.0);
+        assert_eq!(monthly_tax.tax_due, 190.0);
+    }
+}
@@ -0,0 +1,46 @@
+import { invoke } from '@tauri-apps/api/tauri';
+
+export enum AssetClass {
+  STOCK = 'STOCK',
+  UNIT = 'UNIT',
+  REAL_ESTATE_FUND = 'REAL_ESTATE_FUND',
+  ETF = 'ETF',
+  BDR = 'BDR',
+  OPTION = 'OPTION',
+  SUBSCRIPTION_RIGHT = 'SUBSCRIPTION_RIGHT',
+  SUBSCRIPTION_RECEIPT = 'SUBSCRIPTION_RECEIPT',
+  OTHER = 'OTHER'
+}
+
+export interface Asset {
+  id?: number
+  symbol: string
+  asset_class: AssetClass
+  issuer_name?: null|string
+  cnpj?: null|string
+  isin?: null|string
+}
+
+export function getAssetList(): Promise<Asset[]> {
+  return invoke('get_asset_list');
+}
+
+export function newAsset(asset: Asset): Promise<Asset> {
+  return invoke('new_asset', { asset });
+}
+
+export function updateAsset(asset: Asset): Promise<void> {
+  return invoke('update_asset', { asset });
+}
+
+export function deleteAsset(assetId: number): Promise<void> {
+  return invoke('delete_asset', { assetId });
+}
+
+export function importInstruments(filePath: string): Promise<number> {
+  return invoke('import_instruments', { filePath });
+}
+
+export function getUnknownSymbols(symbols: string[]): Promise<string[]> {
+  return invoke('get_unknown_symbols', { symbols });
+}
@@ -1,10 +1,12 @@
 import { invoke } from '@tauri-apps/api/tauri';
 import { Broker } from './brokers';
 import { PageRequest, PageResponse } from '../types/table';
+import { TradeMatch } from './positions';
 
 export interface Brokerage {
   id: number;
   broker: Broker;
+  note_number?: null|string;
   total_settlement_fee: number;
   total_emolument_fee: number;
   total_broker_fee: number;
@@ -51,6 +53,7 @@ export interface BrokerageNote {
   total_sold?: number
   trading_date: string
   orders: BrokerageOrder[]
+  trade_matches?: TradeMatch[]
 }
 
 export function getBrokerageNotePage(pageRequest: PageRequest, brokerId?: number): Promise<PageResponse<Brokerage>> {
@@ -16,3 +16,7 @@ export function getBrokerList(): Promise<Broker[]> {
 export function updateBroker(broker: Broker): Promise<Broker[]> {
   return invoke('update_broker', { broker });
 }
+
+export function deleteBroker(brokerId: number): Promise<void> {
+  return invoke('delete_broker', { brokerId });
+}
@@ -1,13 +1,19 @@
 import { invoke } from '@tauri-apps/api/tauri';
 import { BaseDirectory, readTextFile, writeFile, createDir } from '@tauri-apps/api/fs';
 import { open } from '@tauri-apps/api/dialog';
+import { isAppError } from './error';
 
 export enum ConfigError {
   DB_FILE_NOT_FOUND,
   CANT_OPEN_DB_FILE,
   CANT_CREATE_DB_TABLES,
   NEW_USER,
-  NULL_DATA_DIR
+  NULL_DATA_DIR,
+  CANT_MIGRATE_DATABASE,
+  DATABASE_NEWER_THAN_APP,
+  CANT_READ_RESOURCE_DIR,
+  CANT_READ_CONFIG_FILE,
+  CANT_PARSE_CONFIG_FILE
 }
 
 export type AppConfig = {
@@ -16,6 +22,12 @@ export type AppConfig = {
   menuCollapsed: boolean
 };
 
+export interface OrphanRow {
+  table: string
+  row_id: null|number
+  parent_table: string
+}
+
 const configFilePath = 'consolidacao.config.json';
 
 export async function getConfigFile() {
@@ -64,10 +76,17 @@ export async function updateConfigFile(configFile: AppConfig) {
   });
 }
 
-export async function createDatabase(): Promise<void> {
+export async function createDatabase(): Promise<OrphanRow[]> {
   try {
     return await invoke('create_database');
   } catch (error) {
-    throw ConfigError[error];
+    if (isAppError(error) && error.code in ConfigError) {
+      throw ConfigError[error.code as keyof typeof ConfigError];
+    }
+    throw error;
   }
 }
+
+export function getOrphanRows(): Promise<OrphanRow[]> {
+  return invoke('get_orphan_rows');
+}
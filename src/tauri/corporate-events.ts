@@ -0,0 +1,50 @@
+import { invoke } from '@tauri-apps/api/tauri';
+
+export enum CorporateEventType {
+  SPLIT = 'SPLIT',
+  REVERSE_SPLIT = 'REVERSE_SPLIT',
+  BONUS = 'BONUS',
+  MERGER = 'MERGER',
+  SPIN_OFF = 'SPIN_OFF',
+  TICKER_CHANGE = 'TICKER_CHANGE',
+  SUBSCRIPTION_RIGHT = 'SUBSCRIPTION_RIGHT',
+  SUBSCRIPTION_EXERCISE = 'SUBSCRIPTION_EXERCISE',
+  RECEIPT_CONVERSION = 'RECEIPT_CONVERSION'
+}
+
+export interface CorporateEvent {
+  id?: number
+  symbol: string
+  event_type: CorporateEventType
+  ex_date: string
+  ratio_from: number
+  ratio_to: number
+  unit_cost?: null|number
+  cash_amount?: null|number
+  amount?: null|number
+  targets?: CorporateEventTarget[]
+}
+
+export interface CorporateEventTarget {
+  id?: number
+  symbol: string
+  ratio_from: number
+  ratio_to: number
+  cost_percentage: number
+}
+
+export function getCorporateEvents(symbol?: string): Promise<CorporateEvent[]> {
+  return invoke('get_corporate_events', { symbol });
+}
+
+export function newCorporateEvent(corporateEvent: CorporateEvent): Promise<CorporateEvent> {
+  return invoke('new_corporate_event', { corporateEvent });
+}
+
+export function updateCorporateEvent(corporateEvent: CorporateEvent): Promise<void> {
+  return invoke('update_corporate_event', { corporateEvent });
+}
+
+export function deleteCorporateEvent(corporateEventId: number): Promise<void> {
+  return invoke('delete_corporate_event', { corporateEventId });
+}
@@ -0,0 +1,60 @@
+import { invoke } from '@tauri-apps/api/tauri';
+
+export enum DarfStatus {
+  PENDING = 'PENDING',
+  CARRIED = 'CARRIED',
+  PAID = 'PAID'
+}
+
+export interface Darf {
+  id: number
+  competence: string
+  revenue_code: string
+  tax_amount: number
+  carried_amount: number
+  total_amount: number
+  due_date: string
+  status: DarfStatus
+  payment_date: null|string
+  paid_amount: null|number
+}
+
+export interface LateDarf {
+  principal: number
+  due_date: string
+  payment_date: string
+  days_late: number
+  fine_rate: number
+  fine: number
+  interest_rate: number
+  interest: number
+  total: number
+}
+
+export function getPendingDarfs(): Promise<Darf[]> {
+  return invoke('get_pending_darfs');
+}
+
+export function getDarfs(year: number): Promise<Darf[]> {
+  return invoke('get_darfs', { year });
+}
+
+export function payDarf(darfId: number, paymentDate: string, paidAmount: number): Promise<void> {
+  return invoke('pay_darf', {
+    darfId,
+    paymentDate,
+    paidAmount
+  });
+}
+
+export function importSelicRates(filePath: string): Promise<number> {
+  return invoke('import_selic_rates', { filePath });
+}
+
+export function calcLateDarf(principal: number, competence: string, paymentDate: string): Promise<LateDarf> {
+  return invoke('calc_late_darf', {
+    principal,
+    competence,
+    paymentDate
+  });
+}
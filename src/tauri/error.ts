@@ -0,0 +1,14 @@
+export interface FieldError {
+  field: string
+  code: string
+}
+
+export interface AppError {
+  code: string
+  message: string
+  details: null|FieldError[]
+}
+
+export function isAppError(error: unknown): error is AppError {
+  return typeof error === 'object' && error != null && 'code' in error;
+}
@@ -0,0 +1,56 @@
+import { invoke } from '@tauri-apps/api/tauri';
+import { BrokerageNote } from './brokerage';
+
+export interface BrokerageNotePreview {
+  note_number: string
+  broker_name: string
+  existing_brokerage_id: null|number
+  brokerage_note: BrokerageNote
+  unresolved_orders: UnresolvedOrder[]
+}
+
+export interface UnresolvedOrder {
+  order_index: number
+  specification: string
+}
+
+export interface TradeImportPreview {
+  existing_brokerage_id: null|number
+  brokerage_note: BrokerageNote
+}
+
+export interface UnclassifiedMovement {
+  date: string
+  movement_type: string
+  product: string
+  broker_name: string
+  amount: string
+  value: string
+}
+
+export interface MovementImportResult {
+  imported_income: number
+  imported_corporate_events: number
+  duplicates: number
+  unclassified: UnclassifiedMovement[]
+}
+
+export function previewBrokerageNotePdf(filePath: string): Promise<BrokerageNotePreview[]> {
+  return invoke('preview_brokerage_note_pdf', { filePath });
+}
+
+export function previewB3Trades(filePath: string): Promise<TradeImportPreview[]> {
+  return invoke('preview_b3_trades', { filePath });
+}
+
+export function importBrokerageNotes(brokerageNotes: BrokerageNote[]): Promise<void> {
+  return invoke('import_brokerage_notes', { brokerageNotes });
+}
+
+export function importB3Trades(brokerageNotes: BrokerageNote[]): Promise<void> {
+  return invoke('import_b3_trades', { brokerageNotes });
+}
+
+export function importB3Movements(filePath: string): Promise<MovementImportResult> {
+  return invoke('import_b3_movements', { filePath });
+}
@@ -0,0 +1,42 @@
+import { invoke } from '@tauri-apps/api/tauri';
+import { Broker } from './brokers';
+import { PageRequest, PageResponse } from '../types/table';
+
+export enum IncomeType {
+  DIVIDEND = 'DIVIDEND',
+  INTEREST_ON_EQUITY = 'INTEREST_ON_EQUITY',
+  REAL_ESTATE_FUND_INCOME = 'REAL_ESTATE_FUND_INCOME',
+  OTHER = 'OTHER'
+}
+
+export interface Income {
+  id?: number
+  broker: Broker
+  symbol: string
+  income_type: IncomeType
+  ex_date: string
+  payment_date: string
+  gross_amount: number
+  withholding_tax?: null|number
+  net_amount?: number
+}
+
+export function getIncomePage(pageRequest: PageRequest, brokerId?: number): Promise<PageResponse<Income>> {
+  return invoke('get_income_page', { pageRequest, brokerId });
+}
+
+export function getIncome(incomeId: number): Promise<Income> {
+  return invoke('get_income', { incomeId });
+}
+
+export function newIncome(income: Income): Promise<Income> {
+  return invoke('new_income', { income });
+}
+
+export function updateIncome(income: Income): Promise<Income> {
+  return invoke('update_income', { income });
+}
+
+export function deleteIncome(incomeId: number): Promise<void> {
+  return invoke('delete_income', { incomeId });
+}
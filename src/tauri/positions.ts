@@ -0,0 +1,96 @@
+import { invoke } from '@tauri-apps/api/tauri';
+import { Broker } from './brokers';
+
+export interface Position {
+  symbol: string
+  amount: number
+  average_price: number
+  total_invested: number
+}
+
+export interface RealizedResult {
+  id: number
+  order_id: number
+  broker: Broker
+  trading_date: string
+  symbol: string
+  amount: number
+  average_price: number
+  cost_basis: number
+  proceeds: number
+  result: number
+}
+
+export interface DayTradeResult {
+  id: number
+  broker: Broker
+  trading_date: string
+  symbol: string
+  amount: number
+  purchase_price: number
+  sale_price: number
+  cost_basis: number
+  proceeds: number
+  result: number
+}
+
+export interface CorporateEventResult {
+  id: number
+  corporate_event_id: number
+  ex_date: string
+  symbol: string
+  amount: number
+  cost_basis: number
+  proceeds: number
+  result: number
+}
+
+export interface UncoveredSale {
+  order_id: number
+  broker: Broker
+  trading_date: string
+  symbol: string
+  amount: number
+}
+
+export interface TradeMatch {
+  symbol: string
+  purchased_amount: number
+  sold_amount: number
+  day_trade_amount: number
+  swing_trade_amount: number
+}
+
+export function getPositions(brokerId?: number, date?: string): Promise<Position[]> {
+  return invoke('get_positions', {
+    brokerId,
+    date
+  });
+}
+
+export function getRealizedResults(month?: string, symbol?: string, brokerId?: number): Promise<RealizedResult[]> {
+  return invoke('get_realized_results', {
+    month,
+    symbol,
+    brokerId
+  });
+}
+
+export function getDayTradeResults(month?: string, symbol?: string, brokerId?: number): Promise<DayTradeResult[]> {
+  return invoke('get_day_trade_results', {
+    month,
+    symbol,
+    brokerId
+  });
+}
+
+export function getCorporateEventResults(month?: string, symbol?: string): Promise<CorporateEventResult[]> {
+  return invoke('get_corporate_event_results', {
+    month,
+    symbol
+  });
+}
+
+export function getUncoveredSales(): Promise<UncoveredSale[]> {
+  return invoke('get_uncovered_sales');
+}
@@ -0,0 +1,49 @@
+import { invoke } from '@tauri-apps/api/tauri';
+import { AssetClass } from './assets';
+
+export interface AssetAndRight {
+  group: string
+  code: string
+  symbol: string
+  asset_class: AssetClass
+  cnpj: null|string
+  description: string
+  previous_amount: number
+  previous_value: number
+  amount: number
+  value: number
+}
+
+export enum IncomeSection {
+  EXEMPT = 'EXEMPT',
+  EXCLUSIVE_TAXATION = 'EXCLUSIVE_TAXATION'
+}
+
+export enum IncomeCategory {
+  DIVIDENDS = 'DIVIDENDS',
+  REAL_ESTATE_FUND_INCOME = 'REAL_ESTATE_FUND_INCOME',
+  EXEMPT_STOCK_GAINS = 'EXEMPT_STOCK_GAINS',
+  BONUS_SHARES = 'BONUS_SHARES',
+  INTEREST_ON_EQUITY = 'INTEREST_ON_EQUITY'
+}
+
+export interface IncomeReportItem {
+  section: IncomeSection
+  category: IncomeCategory
+  code: string
+  cnpj: null|string
+  payer_name: null|string
+  amount: number
+}
+
+export function getAssetsAndRightsReport(year: number): Promise<AssetAndRight[]> {
+  return invoke('get_assets_and_rights_report', { year });
+}
+
+export function exportAssetsAndRightsReport(year: number, filePath: string): Promise<void> {
+  return invoke('export_assets_and_rights_report', { year, filePath });
+}
+
+export function getIncomeReport(year: number): Promise<IncomeReportItem[]> {
+  return invoke('get_income_report', { year });
+}
@@ -0,0 +1,46 @@
+import { invoke } from '@tauri-apps/api/tauri';
+
+export interface MonthlyTax {
+  month: string
+  stock_sales: number
+  etf_sales: number
+  bdr_sales: number
+  other_sales: number
+  exempt: boolean
+  stock_result: number
+  swing_trade_result: number
+  swing_trade_loss_offset: number
+  swing_trade_tax: number
+  day_trade_result: number
+  day_trade_loss_offset: number
+  day_trade_tax: number
+  real_estate_fund_sales: number
+  real_estate_fund_result: number
+  real_estate_fund_loss_offset: number
+  real_estate_fund_tax: number
+  withheld_income_tax: number
+  tax_due: number
+}
+
+export enum LossCategory {
+  SWING_TRADE = 'SWING_TRADE',
+  DAY_TRADE = 'DAY_TRADE',
+  REAL_ESTATE_FUND = 'REAL_ESTATE_FUND'
+}
+
+export interface AccumulatedLoss {
+  month: string
+  category: LossCategory
+  available: number
+  incurred: number
+  consumed: number
+  remaining: number
+}
+
+export function getMonthlyTax(year: number): Promise<MonthlyTax[]> {
+  return invoke('get_monthly_tax', { year });
+}
+
+export function getAccumulatedLosses(year: number): Promise<AccumulatedLoss[]> {
+  return invoke('get_accumulated_losses', { year });
+}